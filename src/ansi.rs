@@ -0,0 +1,301 @@
+//! Escape sequences in the output of the commands: SGR ones become the style of the text, anything else,
+//! like cursor movements or window titles, is dropped so it can't break the interface.
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+/// Single character CSI of the 8-bit controls.
+const CSI: char = '\u{9b}';
+
+/// Piece of text, split at the escape sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Text(&'a str),
+    /// Parameters of a Select Graphic Rendition sequence.
+    Sgr(&'a str),
+    /// Back to the start of the line, the text after it writes over what was before.
+    CarriageReturn,
+}
+
+/// Splits the text at the escape sequences and control characters, leaving out the ones without meaning here.
+struct Tokens<'a> {
+    text: &'a str,
+}
+
+impl<'a> Tokens<'a> {
+    /// Rest of the text after `length` bytes, returning the skipped part.
+    fn advance(&mut self, length: usize) -> &'a str {
+        let (skipped, rest) = self.text.split_at(length.min(self.text.len()));
+        self.text = rest;
+        skipped
+    }
+
+    /// Length of a string sequence, like OSC, up to its BEL or ST terminator, the whole text if unterminated.
+    fn string_length(&self, start: usize) -> usize {
+        let mut chars = self.text[start..].char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                BEL | '\u{9c}' => return start + index + c.len_utf8(),
+                ESC if chars.peek().is_some_and(|(_, next)| *next == '\\') => return start + index + 2,
+                _ => {},
+            }
+        }
+        self.text.len()
+    }
+
+    /// Control sequence starting after the introducer, as its parameters, whether it is SGR, and its length.
+    fn csi(&self, start: usize) -> (&'a str, bool, usize) {
+        let text = self.text;
+        for (index, c) in text[start..].char_indices() {
+            match c {
+                // Parameters, then intermediate bytes.
+                '\x30'..='\x3f' | '\x20'..='\x2f' => {},
+                '\x40'..='\x7e' => {
+                    let parameters = &text[start..start + index];
+                    let sgr = c == 'm' && parameters.bytes().all(|b| b.is_ascii_digit() || b == b';' || b == b':');
+                    return (parameters, sgr, start + index + 1);
+                },
+                // Anything else cancels the sequence.
+                _ => return ("", false, start + index),
+            }
+        }
+        ("", false, text.len())
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut chars = self.text.chars();
+            let c = chars.next()?;
+
+            if !c.is_control() || c == '\t' {
+                let end = self.text.find(|c: char| c.is_control() && c != '\t').unwrap_or(self.text.len());
+                return Some(Token::Text(self.advance(end)));
+            }
+
+            match (c, chars.next()) {
+                (ESC, Some('[')) | (CSI, _) => {
+                    let (parameters, sgr, length) = self.csi(c.len_utf8() + usize::from(c == ESC));
+                    self.advance(length);
+                    if sgr {
+                        return Some(Token::Sgr(parameters));
+                    }
+                },
+                // OSC, DCS, SOS, PM and APC, and their 8-bit controls.
+                (ESC, Some(']' | 'P' | 'X' | '^' | '_')) => {
+                    let length = self.string_length(2);
+                    self.advance(length);
+                },
+                ('\u{90}' | '\u{98}' | '\u{9d}' | '\u{9e}' | '\u{9f}', _) => {
+                    let length = self.string_length(c.len_utf8());
+                    self.advance(length);
+                },
+                // Character set designations take one more character.
+                (ESC, Some(designation @ ('(' | ')' | '*' | '+' | '#' | '%'))) => {
+                    let next = chars.next().map_or(0, char::len_utf8);
+                    self.advance(1 + designation.len_utf8() + next);
+                },
+                (ESC, Some(next)) => {
+                    self.advance(1 + next.len_utf8());
+                },
+                ('\r', _) => {
+                    self.advance(1);
+                    return Some(Token::CarriageReturn);
+                },
+                // Bells, backspaces and the rest of the controls.
+                _ => {
+                    self.advance(c.len_utf8());
+                },
+            }
+        }
+    }
+}
+
+/// Color of the SGR parameters after 38, 48 or 58: `5;n` for the 256 colors, `2;r;g;b` for truecolor.
+fn extended_color(parameters: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    let byte = |value: u16| u8::try_from(value).ok();
+    match parameters.next()? {
+        5 => Some(Color::Indexed(byte(parameters.next()?)?)),
+        2 => {
+            let (red, green, blue) = (parameters.next()?, parameters.next()?, parameters.next()?);
+            Some(Color::Rgb(byte(red)?, byte(green)?, byte(blue)?))
+        },
+        _ => None,
+    }
+}
+
+/// Style with the modifiers given back as they are in `base`.
+fn reset(style: Style, base: Style, modifier: Modifier) -> Style {
+    let add_modifier = (style.add_modifier - modifier) | (base.add_modifier & modifier);
+    Style { add_modifier, ..style }
+}
+
+/// Style of the text after the SGR parameters, `base` is the style it resets to.
+fn apply(parameters: &str, mut style: Style, base: Style) -> Style {
+    if parameters.is_empty() {
+        return base;
+    }
+
+    // Sub-parameters, like `38:2::255:0:0`, are read as parameters, skipping the empty color space.
+    let colon = parameters.contains(':');
+    let mut values = parameters
+        .split([';', ':'])
+        .filter(|value| !colon || !value.is_empty())
+        .map(|value| value.parse::<u16>().unwrap_or(0));
+
+    while let Some(value) = values.next() {
+        style = match value {
+            0 => base,
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 | 21 => style.add_modifier(Modifier::UNDERLINED),
+            5 => style.add_modifier(Modifier::SLOW_BLINK),
+            6 => style.add_modifier(Modifier::RAPID_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            8 => style.add_modifier(Modifier::HIDDEN),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => reset(style, base, Modifier::BOLD | Modifier::DIM),
+            23 => reset(style, base, Modifier::ITALIC),
+            24 => reset(style, base, Modifier::UNDERLINED),
+            25 => reset(style, base, Modifier::SLOW_BLINK | Modifier::RAPID_BLINK),
+            27 => reset(style, base, Modifier::REVERSED),
+            28 => reset(style, base, Modifier::HIDDEN),
+            29 => reset(style, base, Modifier::CROSSED_OUT),
+            30..=37 => style.fg(Color::Indexed((value - 30) as u8)),
+            90..=97 => style.fg(Color::Indexed((value - 90 + 8) as u8)),
+            40..=47 => style.bg(Color::Indexed((value - 40) as u8)),
+            100..=107 => style.bg(Color::Indexed((value - 100 + 8) as u8)),
+            38 | 48 | 58 => {
+                // The rest can't be told apart from the color if it is malformed.
+                let Some(color) = extended_color(&mut values) else {
+                    break;
+                };
+                match value {
+                    38 => style.fg(color),
+                    48 => style.bg(color),
+                    _ => style.underline_color(color),
+                }
+            },
+            39 => Style { fg: base.fg, ..style },
+            49 => Style { bg: base.bg, ..style },
+            59 => Style { underline_color: base.underline_color, ..style },
+            _ => style,
+        };
+    }
+    style
+}
+
+/// Style of the output, kept from one line to the next as terminals do.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ansi {
+    base: Style,
+    style: Style,
+}
+
+impl Ansi {
+    /// Output starting with the style given, `ESC[0m` goes back to it.
+    pub fn new(base: Style) -> Self {
+        Self { base, style: base }
+    }
+
+    /// Line of text with the style of its SGR sequences, the other sequences are dropped.
+    pub fn line(&mut self, text: &str) -> Line<'static> {
+        let mut spans: Vec<Span<'static>> = vec![];
+        for token in (Tokens { text }) {
+            match token {
+                Token::Text(text) => match spans.last_mut() {
+                    Some(span) if span.style == self.style => span.content.to_mut().push_str(text),
+                    _ => spans.push(Span::styled(text.to_string(), self.style)),
+                },
+                Token::Sgr(parameters) => self.style = apply(parameters, self.style, self.base),
+                Token::CarriageReturn => spans.clear(),
+            }
+        }
+        Line::from(spans)
+    }
+}
+
+/// Text without any escape sequence or control character, tabs aside.
+pub fn strip(text: &str) -> String {
+    Tokens { text }
+        .filter_map(|token| match token {
+            Token::Text(text) => Some(text),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens() {
+        for (text, tokens) in [
+            ("plain\ttext", vec![Token::Text("plain\ttext")]),
+            ("\x1b[1;31mred\x1b[m", vec![Token::Sgr("1;31"), Token::Text("red"), Token::Sgr("")]),
+            ("\x1b[2K\x1b[1Aup\x1b[?25l", vec![Token::Text("up")]),
+            ("\x1b]0;title\x07a\x1b]8;;http://x\x1b\\b", vec![Token::Text("a"), Token::Text("b")]),
+            ("\x1b(Bx\x1b7y\x08\x07", vec![Token::Text("x"), Token::Text("y")]),
+            ("50%\r100%", vec![Token::Text("50%"), Token::CarriageReturn, Token::Text("100%")]),
+            ("\u{9b}32mé\x1b[", vec![Token::Sgr("32"), Token::Text("é")]),
+            ("\x1b[1;\x1b[mx", vec![Token::Sgr(""), Token::Text("x")]),
+            ("\x1b]unterminated", vec![]),
+        ] {
+            assert_eq!(Tokens { text }.collect::<Vec<_>>(), tokens, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let base = Style::default().fg(Color::Gray);
+        for (parameters, style) in [
+            ("", base),
+            ("1;4", base.add_modifier(Modifier::BOLD | Modifier::UNDERLINED)),
+            ("31;42", base.fg(Color::Indexed(1)).bg(Color::Indexed(2))),
+            ("91;107", base.fg(Color::Indexed(9)).bg(Color::Indexed(15))),
+            ("38;5;208", base.fg(Color::Indexed(208))),
+            ("38;2;255;128;0;1", base.fg(Color::Rgb(255, 128, 0)).add_modifier(Modifier::BOLD)),
+            ("38:2::1:2:3", base.fg(Color::Rgb(1, 2, 3))),
+            ("48;5;17", base.bg(Color::Indexed(17))),
+            ("58;5;1", base.underline_color(Color::Indexed(1))),
+            ("31;39", base),
+            ("1;22", base),
+            ("38;5;300;1", base),
+            ("38;2;1", base),
+            ("1;0;3", base.add_modifier(Modifier::ITALIC)),
+        ] {
+            assert_eq!(apply(parameters, base, base), style, "{}", parameters);
+        }
+    }
+
+    #[test]
+    fn test_line() {
+        let mut ansi = Ansi::default();
+        let line = ansi.line("\x1b[32mok\x1b[0m: \x1b[1mall\x1b[22m done\x1b[31m");
+        assert_eq!(
+            line.spans,
+            vec![
+                Span::styled("ok", Style::default().fg(Color::Indexed(2))),
+                Span::raw(": "),
+                Span::styled("all", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" done"),
+            ]
+        );
+
+        // The color goes on to the next line until it is reset.
+        assert_eq!(ansi.line("error").spans, vec![Span::styled("error", Style::default().fg(Color::Indexed(1)))]);
+        assert_eq!(ansi.line("\x1b[Kdownloading 10%\rdone").spans, vec![Span::styled("done", Style::default().fg(Color::Indexed(1)))]);
+    }
+
+    #[test]
+    fn test_strip() {
+        assert_eq!(strip("\x1b[1;32m* main\x1b[m\x1b]0;x\x07"), "* main");
+    }
+}
@@ -1,11 +1,24 @@
 use std::error;
+use std::sync::Arc;
+use std::time::Duration;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
 use ratatui::layout::{Constraint, Layout, Position, Rect};
 use ratatui::Frame;
 
-use crate::ui::commands::Command;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::cheat;
+use crate::completion;
+use crate::config::Config;
+use crate::danger::Danger;
 use crate::event::Event;
-use crate::ui::{table::CommandsTable, state::UIComponent, search::SearchBox, template::Template};
+use crate::execution::Execution;
+use crate::pty::{Pty, PtyEvent};
+use crate::generator::Generators;
+use crate::history::{self, History};
+use crate::search::{Hit, MatchKind, Query, Searcher};
+use crate::table::Table;
+use crate::ui::{table::CommandsTable, state::UIComponent, search::SearchBox, template::{Accepted, Template}, terminal::TerminalPane};
 
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
@@ -15,6 +28,7 @@ pub enum AppFocus {
     Table,
     Search,
     Template,
+    Terminal,
     None
 }
 
@@ -24,32 +38,68 @@ pub struct App<'a> {
     /// Is the application running?
     pub running: bool,
     /// State of the table
-    pub table_state: CommandsTable<'a, Command>,
+    pub table_state: CommandsTable<'a, Hit>,
     // /// State of the search bar
     pub search_state: SearchBox<'a>,
     // /// State of the Template view
     pub template_state: Template,
+    /// State of the terminal of the interactive commands
+    pub terminal_state: TerminalPane,
     /// Flag to signal the attention of the user
     /// it will have one value for each widget that
     /// will indicate where the user is currently
     pub attention: AppFocus,
+    /// Handle to the thread matching the commands
+    pub searcher: Searcher,
+    /// Commands selected so far, saved after each selection
+    pub history: History,
+    /// Command accepted, with its placeholders filled, printed once the application exits
+    pub accepted: Option<String>,
+    /// Generators of the variables of the command being filled
+    pub generators: Generators,
+    /// Choose the only result of the initial query without asking
+    pub select_1: bool,
+    /// Quit if the initial query has no results
+    pub exit_0: bool,
+    /// Sender of the application events, to report back from other threads
+    pub events: UnboundedSender<Event>,
+    /// Command run from the form, its output shown in the Template view
+    pub execution: Option<Execution>,
 }
 
 impl App<'_> {
-    /// Constructs a new instance of [`App`].
-    pub fn new() -> Self {
+    /// Constructs a new instance of [`App`], commands are added
+    /// through the injector of the [`Searcher`].
+    ///
+    /// `sender` is used to wake up the application when there are new results.
+    pub fn new(config: &Config, history: History, sender: UnboundedSender<Event>) -> Self {
+        let table = Arc::new(Table::default());
+        let timeout = Duration::from_secs(config.variables.generator_timeout);
+        let generators = Generators::new(sender.clone(), timeout);
+        let events = sender.clone();
+        let searcher = Searcher::spawn(table.clone(), &config.search, move || {
+            let _ = sender.send(Event::Tick);
+        });
+        searcher.set_history(history.clone());
+        let mut template_state = Template::default();
+        template_state.shell = config.shell();
+        template_state.danger = Danger::new(&config.danger);
+
         Self {
             running: true,
-            table_state: CommandsTable::new(vec![
-                Command {
-                    command: "ls".into(), 
-                    description: "List directory contents".into(),
-                    tags: vec!["list".into(), "directory".into(), "contents".into()],
-                }; 10
-            ]),
+            table_state: CommandsTable::new(table.clone()),
             search_state: SearchBox::default(),
-            template_state: Template::default(),
-            attention: AppFocus::None,
+            template_state,
+            terminal_state: TerminalPane::default(),
+            attention: AppFocus::Search,
+            searcher,
+            history,
+            accepted: None,
+            generators,
+            select_1: false,
+            exit_0: false,
+            events,
+            execution: None,
         }
     }
 
@@ -64,26 +114,179 @@ impl App<'_> {
         self.search_state.area = Some(search);
         self.table_state.area = Some(commands);
         self.template_state.area = Some(template);
+        self.terminal_state.area = Some(template.union(commands));
     }
 
     /// Handles the tick event of the terminal.
-    pub fn tick(&self) {}
+    pub fn tick(&mut self) {
+        self.table_state.refresh();
+    }
 
     /// Set running to false to quit the application.
     pub fn quit(&mut self) {
         self.running = false;
     }
 
+    /// Records the use of the chosen command and asks for its placeholders.
+    fn select(&mut self, hit: Hit) {
+        self.history.record(&hit.command.command, history::now());
+        self.save_history();
+        self.searcher.set_history(self.history.clone());
+
+        self.template_state.fill(Arc::unwrap_or_clone(hit.command), &self.history);
+        self.attention = AppFocus::Template;
+        self.generate();
+    }
+
+    /// Searches the text of the search box, if it changed.
+    fn search(&mut self) {
+        if let Some(text) = self.search_state.take_query() {
+            match Query::parse(&text) {
+                Ok(query) => {
+                    self.search_state.set_error(None::<&str>);
+                    self.searcher.search(query);
+                },
+                Err(err) => self.search_state.set_error(Some(err)),
+            }
+        }
+    }
+
+    /// Writes the text in the search box and searches it.
+    pub fn set_query(&mut self, text: &str) {
+        self.search_state.set_text(text);
+        self.search();
+    }
+
+    /// Runs the generator the focused variable needs, if any, and lists the path typed in it.
+    fn generate(&mut self) {
+        if let Some(request) = self.template_state.pending_generator() {
+            let id = self.generators.run(&request.variable, &request.command, request.env.clone());
+            self.template_state.started(request, id);
+        }
+        if let Some(listing) = self.template_state.take_listing() {
+            completion::list(listing, self.events.clone());
+        }
+    }
+
+    /// Saves the history, reporting errors in the status bar.
+    fn save_history(&mut self) {
+        if let Err(err) = self.history.save() {
+            self.table_state.status = Some(err.to_string());
+        }
+    }
+
+    /// Leaves the form of the command being filled.
+    fn close_form(&mut self) {
+        self.generators.cancel();
+        self.template_state.close();
+        self.attention = AppFocus::Search;
+    }
+
     fn key_handling(&mut self, key: KeyEvent) {
         match self.attention {
+            AppFocus::Table | AppFocus::Search if key.code == KeyCode::Enter => {
+                self.table_state.key(key);
+                if let Some(hit) = self.table_state.take_chosen() {
+                    self.select(hit);
+                }
+            },
             AppFocus::Table => self.table_state.key(key),
-            AppFocus::Search => self.search_state.key(key),
-            AppFocus::Template => self.template_state.key(key),
+            // The results can be browsed without leaving the search box.
+            AppFocus::Search if matches!(key.code, KeyCode::Up | KeyCode::Down) => self.table_state.key(key),
+            AppFocus::Search => {
+                self.search_state.key(key);
+                self.search();
+            },
+            AppFocus::Template => {
+                self.template_state.key(key);
+                self.form_changed();
+            },
+            AppFocus::Terminal => self.terminal_state.key(key),
             _ => {}
         }
     }
 
+    /// Takes what the form left after a change: an error, values to forget, and the command once accepted.
+    fn form_changed(&mut self) {
+        if let Some(err) = self.template_state.take_error() {
+            self.table_state.status = Some(err);
+        }
+
+        let forgotten = self.template_state.take_forgotten();
+        for (variable, value) in &forgotten {
+            self.history.forget(variable, Some(value));
+        }
+        if !forgotten.is_empty() {
+            self.save_history();
+        }
+
+        if let Some(accepted) = self.template_state.take_accepted() {
+            self.history.record_values(&accepted.command.command, &accepted.values, history::now());
+            self.save_history();
+            self.run(accepted);
+        } else {
+            self.generate();
+        }
+    }
+
+    /// Prints the accepted command on exit, or runs it in a terminal if it is [`cheat::INTERACTIVE`] and
+    /// capturing its output otherwise.
+    fn run(&mut self, accepted: Accepted) {
+        self.close_form();
+        if accepted.execute && accepted.command.tags.iter().any(|tag| tag == cheat::INTERACTIVE) {
+            self.open_terminal(&accepted.rendered);
+        } else if accepted.execute {
+            self.execute(&accepted.rendered);
+        } else {
+            self.accepted = Some(accepted.rendered);
+            self.quit();
+        }
+    }
+
+    /// Runs the command through the shell, showing its output in the Template view.
+    fn execute(&mut self, command: &str) {
+        self.template_state.start_output(command);
+        self.execution = Some(Execution::spawn(command, self.template_state.shell, self.events.clone()));
+        self.attention = AppFocus::Template;
+    }
+
+    /// Runs the interactive command in a terminal, the keys go to it until it exits.
+    fn open_terminal(&mut self, command: &str) {
+        match Pty::spawn(command, self.template_state.shell, self.terminal_state.size(), self.events.clone()) {
+            Ok(pty) => {
+                self.terminal_state.open(pty);
+                self.attention = AppFocus::Terminal;
+            },
+            Err(err) => self.table_state.status = Some(err.to_string()),
+        }
+    }
+
+    /// Leaves the output of the command run, killing it if it is still running.
+    fn close_output(&mut self) {
+        self.execution = None;
+        self.template_state.close_output();
+        self.attention = AppFocus::Search;
+    }
+
+    /// Applies `--exit-0` and `--select-1` to the first results of the initial query.
+    fn settled(&mut self, hits: Vec<Hit>) {
+        match hits.as_slice() {
+            [] if self.exit_0 => self.quit(),
+            [hit] if self.select_1 => {
+                self.select(hit.clone());
+                self.template_state.accept_if_complete();
+                self.form_changed();
+            },
+            _ => {},
+        }
+    }
+
     fn click(&mut self, event: MouseEvent) {
+        // The terminal keeps the focus until its command exits.
+        if self.terminal_state.is_open() {
+            return;
+        }
+
         if self.table_state.contains(Position::new( event.column, event.row )) {
             self.attention = AppFocus::Table;
             self.table_state.click(event);
@@ -98,12 +301,42 @@ impl App<'_> {
         }
     }
 
-    fn resize(&self, _: u16, _: u16) {
-        unimplemented!("Resize not implemented yet");
-    }
+    /// Nothing to do here: every event is followed by a draw, which lays out the views for the new size
+    /// and resizes the terminal of the interactive command.
+    fn resize(&self, _: u16, _: u16) {}
 
     pub fn handle(&mut self, event: Event) {
         match event {
+            // Every key goes to the command in the terminal, even the ones to quit.
+            Event::Key(key) if self.attention == AppFocus::Terminal => {
+                self.key_handling(key);
+            },
+            // The confirmation of a dangerous command takes the keys, Esc goes back to the form.
+            Event::Key(key)
+                if self.template_state.is_confirming()
+                    && !(key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)) =>
+            {
+                self.template_state.key(key);
+                self.form_changed();
+            },
+            // Leaves the placeholders without quitting.
+            Event::Key(key) if key.code == KeyCode::Esc && self.template_state.is_filling() => {
+                self.close_form();
+            },
+            // Ctrl-C kills the command running instead of quitting.
+            Event::Key(key)
+                if key.code == KeyCode::Char('c')
+                    && key.modifiers.contains(KeyModifiers::CONTROL)
+                    && self.attention == AppFocus::Template
+                    && self.template_state.is_running() =>
+            {
+                if let Some(execution) = &self.execution {
+                    execution.kill();
+                }
+            },
+            Event::Key(key) if key.code == KeyCode::Esc && self.template_state.is_showing_output() => {
+                self.close_output();
+            },
             Event::Key(key) 
                 if (key.code == KeyCode::Esc) 
                     || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))  
@@ -119,7 +352,52 @@ impl App<'_> {
             Event::Resize(x, y) => {
                 self.resize(x, y);
             },
-            _ => {}
+            Event::Loaded(errors) => {
+                // A clean load keeps the status, like the error of a broken history.
+                if let Some(err) = errors.first() {
+                    self.table_state.status = Some(match errors.len() {
+                        1 => err.to_string(),
+                        n => format!("{} (and {} more errors)", err, n - 1),
+                    });
+                }
+
+                if self.select_1 || self.exit_0 {
+                    let events = self.events.clone();
+                    // Commands that only sound like the query are neither picked nor count as found.
+                    self.searcher.settle(move |hits| {
+                        let fuzzy = hits.iter().filter(|hit| hit.kind == MatchKind::Fuzzy).take(2).cloned().collect();
+                        let _ = events.send(Event::Settled(fuzzy));
+                    });
+                }
+            },
+            Event::Generated(generated) => {
+                self.template_state.generated(generated);
+            },
+            Event::Listed(listed) => {
+                self.template_state.listed(listed);
+            },
+            Event::Settled(hits) => {
+                self.settled(hits);
+            },
+            Event::Executed(executed) => {
+                if self.execution.as_ref().is_some_and(|execution| execution.id == executed.id) {
+                    self.template_state.output(executed.progress);
+                }
+            },
+            Event::Pty(PtyEvent::Output { .. }) => {},
+            Event::Pty(PtyEvent::Exited { id, status, success }) => {
+                let Some(pty) = self.terminal_state.running(id) else {
+                    return;
+                };
+                if !success {
+                    self.table_state.status = Some(format!("{}: {}", pty.command, status));
+                }
+                self.terminal_state.close();
+                self.attention = AppFocus::Search;
+            },
+            Event::Tick => {
+                self.tick();
+            },
         }
     }
 
@@ -127,5 +405,54 @@ impl App<'_> {
         self.table_state.render(frame);
         self.search_state.render(frame);
         self.template_state.render(frame);
+        self.terminal_state.render(frame);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    use crate::ui::commands::Command;
+
+    fn new_app() -> App<'static> {
+        let (sender, _) = tokio::sync::mpsc::unbounded_channel();
+        App::new(&Config::default(), History::default(), sender)
+    }
+
+    fn accepted(tags: &[&str], execute: bool) -> Accepted {
+        let command = Command {
+            command: "true".into(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            description: String::new(),
+            variables: vec![],
+            source: None,
+        };
+        Accepted { command, values: HashMap::new(), rendered: "true".into(), execute }
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        // Interactive commands get a terminal.
+        let mut app = new_app();
+        app.run(accepted(&["top", cheat::INTERACTIVE], true));
+        assert!(app.terminal_state.is_open());
+        assert!(app.execution.is_none());
+        assert_eq!(app.attention, AppFocus::Terminal);
+
+        // The others have their output captured.
+        let mut app = new_app();
+        app.run(accepted(&["top"], true));
+        assert!(!app.terminal_state.is_open());
+        assert!(app.execution.is_some());
+        assert_eq!(app.attention, AppFocus::Template);
+
+        // Any of them is printed when it isn't run.
+        let mut app = new_app();
+        app.run(accepted(&[cheat::INTERACTIVE], false));
+        assert!(!app.terminal_state.is_open());
+        assert_eq!(app.accepted.as_deref(), Some("true"));
+        assert!(!app.running);
     }
 }
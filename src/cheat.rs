@@ -0,0 +1,432 @@
+//! Parser for navi compatible `.cheat` files.
+//!
+//! A cheat file is a sequence of blocks, each one starting with a `% tags` line. Inside a block:
+//!
+//! ```text
+//! % git, code
+//!
+//! # Change branch
+//! git checkout <branch>
+//!
+//! $ branch: git branch | awk '{print $NF}'
+//! ```
+//!
+//! - `# text` sets the description of the next command.
+//! - Any other non empty line is part of a command, consecutive lines form a single command.
+//! - `$ name: generator` declares a variable for the commands of the block.
+//! - `$ name: generator --- options` gives the variable a type and a default, see [`Options`].
+//! - Commands can use the template language of [`crate::template`], errors point to the line and column.
+//! - `;` starts a comment, `@` (extending other cheats) is not supported and ignored.
+//!
+//! Commands run with Ctrl-O show their output below the form. Programs that take the screen, like `htop` or
+//! `git rebase -i`, need the [`INTERACTIVE`] tag in their block to run in a terminal pane instead.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+use crate::template;
+use crate::ui::commands::{Command, Pattern, Source, Variable, VariableType};
+
+/// Extension of the files that contain commands.
+pub const EXTENSION: &str = "cheat";
+
+/// Tag of the commands run in a terminal pane, instead of having their output captured.
+pub const INTERACTIVE: &str = "interactive";
+
+/// Parses the content of a cheat file, `path` is only used to report errors and
+/// to annotate each command with its source.
+pub fn parse(path: &Path, content: &str) -> Result<Vec<Command>, Error> {
+    let mut parser = Parser::new(path);
+
+    for (index, line) in content.lines().enumerate() {
+        parser.line(index + 1, line)?;
+    }
+
+    parser.finish()
+}
+
+/// Folder with the cheat files, `$NAVU_PATH` if set or `navu/cheats` under the XDG data directory.
+pub fn default_path() -> PathBuf {
+    std::env::var_os("NAVU_PATH")
+        .map(PathBuf::from)
+        .or_else(|| dirs::data_dir().map(|data| data.join("navu").join("cheats")))
+        .unwrap_or_else(|| PathBuf::from("cheats"))
+}
+
+/// Whether the path looks like a cheat file.
+pub fn is_cheat(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == EXTENSION)
+}
+
+/// Options of a variable, written after `---` in its declaration:
+///
+/// ```text
+/// $ env: --- --type enum --choices dev,staging,prod --default dev
+/// $ replicas: --- --type int --required
+/// $ tag: git tag --- --type regex --pattern '^v[0-9]+' --optional
+/// $ archive: --- --type path
+/// $ target: --- --type dir
+/// ```
+///
+/// `--choices` and `--pattern` are enough to set the type. Other options, like the ones navi
+/// gives to fzf, are ignored.
+#[derive(Debug, Default)]
+struct Options {
+    kind: VariableType,
+    default: Option<String>,
+    required: bool,
+}
+
+impl Options {
+    fn parse(options: &str) -> Result<Self, String> {
+        let words = shell_words::split(options).map_err(|err| format!("invalid variable options: {}", err))?;
+        let mut words = words.into_iter();
+
+        let mut kind = None;
+        let mut choices = None;
+        let mut pattern = None;
+        let mut parsed = Options::default();
+
+        while let Some(word) = words.next() {
+            let mut value = || words.next().ok_or_else(|| format!("missing value for `{}`", word));
+
+            match word.as_str() {
+                "--type" => kind = Some(value()?),
+                "--choices" => choices = Some(value()?),
+                "--pattern" => pattern = Some(value()?),
+                "--default" => parsed.default = Some(value()?),
+                "--required" => parsed.required = true,
+                "--optional" => parsed.required = false,
+                _ => {},
+            }
+        }
+
+        let kind = kind.as_deref().or(choices.as_ref().map(|_| "enum")).or(pattern.as_ref().map(|_| "regex"));
+        parsed.kind = match (kind, choices, pattern) {
+            (None | Some("string"), None, None) => VariableType::String,
+            (Some("int" | "integer"), None, None) => VariableType::Integer,
+            (Some("path"), None, None) => VariableType::Path,
+            (Some("dir" | "directory"), None, None) => VariableType::Directory,
+            (Some("enum"), Some(choices), None) => {
+                VariableType::Enum(choices.split(',').map(str::trim).filter(|choice| !choice.is_empty()).map(String::from).collect())
+            },
+            (Some("enum"), None, _) => return Err("`--type enum` needs `--choices`".to_string()),
+            (Some("regex"), None, Some(pattern)) => {
+                let regex = regex::Regex::new(&pattern).map_err(|err| format!("invalid pattern `{}`: {}", pattern, err))?;
+                VariableType::Regex(Pattern(regex))
+            },
+            (Some("regex"), _, None) => return Err("`--type regex` needs `--pattern`".to_string()),
+            (Some(kind @ ("string" | "int" | "integer" | "path" | "dir" | "directory" | "enum" | "regex")), _, _) => {
+                return Err(format!("`--choices` and `--pattern` don't apply to `--type {}`", kind));
+            },
+            (Some(kind), _, _) => return Err(format!("unknown variable type `{}`", kind)),
+            (None, _, _) => unreachable!("the type is set by `--choices` or `--pattern`"),
+        };
+
+        Ok(parsed)
+    }
+}
+
+struct Parser {
+    path: PathBuf,
+    tags: Vec<String>,
+    description: Option<String>,
+    variables: Vec<Variable>,
+
+    /// Command being read, with the line where it started.
+    current: Option<(usize, Vec<String>)>,
+    /// Commands of the current block, waiting for the variables of the block.
+    block: Vec<Command>,
+    commands: Vec<Command>,
+    in_fence: bool,
+}
+
+impl Parser {
+    fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            tags: vec![],
+            description: None,
+            variables: vec![],
+            current: None,
+            block: vec![],
+            commands: vec![],
+            in_fence: false,
+        }
+    }
+
+    fn error(&self, line: usize, message: impl Into<String>) -> Error {
+        Error::Parse {
+            path: self.path.clone(),
+            line,
+            message: message.into(),
+        }
+    }
+
+    fn line(&mut self, number: usize, line: &str) -> Result<(), Error> {
+        let trimmed = line.trim();
+
+        // Markdown fences are allowed around commands, they are only decoration.
+        if trimmed.starts_with("```") {
+            self.in_fence = !self.in_fence;
+            self.end_command()?;
+            return Ok(());
+        }
+
+        if self.in_fence {
+            if !trimmed.is_empty() {
+                self.push_command_line(number, line);
+            }
+            return Ok(());
+        }
+
+        if trimmed.is_empty() {
+            self.end_command()?;
+            return Ok(());
+        }
+
+        match trimmed.chars().next() {
+            Some('%') => {
+                self.end_block()?;
+                self.tags = trimmed[1..]
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(String::from)
+                    .collect();
+            },
+            Some('#') => {
+                self.end_command()?;
+                self.description = Some(trimmed[1..].trim().to_string());
+            },
+            Some('$') => {
+                self.end_command()?;
+                let variable = self.variable(number, &trimmed[1..])?;
+                if self.variables.iter().any(|declared| declared.name == variable.name) {
+                    return Err(self.error(number, format!("variable `{}` declared twice", variable.name)));
+                }
+                self.variables.push(variable);
+            },
+            Some(';') | Some('@') => {},
+            _ => self.push_command_line(number, line),
+        }
+
+        Ok(())
+    }
+
+    fn variable(&self, number: usize, declaration: &str) -> Result<Variable, Error> {
+        let (name, generator) = declaration
+            .split_once(':')
+            .ok_or_else(|| self.error(number, "expected `$ name: command` in variable declaration"))?;
+
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(self.error(number, "missing variable name"));
+        }
+
+        if let Some(invalid) = name.chars().find(|c| !is_variable_char(*c)) {
+            return Err(self.error(number, format!("invalid character `{}` in variable name `{}`", invalid, name)));
+        }
+
+        let (generator, options) = generator.split_once("---").unwrap_or((generator, ""));
+        let options = Options::parse(options).map_err(|message| self.error(number, message))?;
+        let generator = generator.trim();
+
+        let variable = Variable {
+            name: name.to_string(),
+            generator: (!generator.is_empty()).then(|| generator.to_string()),
+            kind: options.kind,
+            default: options.default,
+            required: options.required,
+        };
+
+        if let Some(default) = &variable.default {
+            if let Err(message) = variable.validate(default) {
+                return Err(self.error(number, format!("invalid default `{}` for `{}`: {}", default, name, message)));
+            }
+        }
+
+        Ok(variable)
+    }
+
+    fn push_command_line(&mut self, number: usize, line: &str) {
+        match &mut self.current {
+            Some((_, lines)) => lines.push(line.trim_end().to_string()),
+            None => self.current = Some((number, vec![line.trim_end().to_string()])),
+        }
+    }
+
+    fn end_command(&mut self) -> Result<(), Error> {
+        if let Some((line, lines)) = self.current.take() {
+            let command = lines.join("\n");
+            if let Err(err) = template::parse(&command) {
+                let (offset, column) = err.position(&command);
+                return Err(Error::Template {
+                    path: self.path.clone(),
+                    line: line + offset,
+                    column: column + 1,
+                    message: err.message,
+                });
+            }
+
+            self.block.push(Command {
+                command,
+                tags: self.tags.clone(),
+                description: self.description.take().unwrap_or_default(),
+                variables: vec![],
+                source: Some(Source {
+                    path: self.path.clone(),
+                    line,
+                }),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn end_block(&mut self) -> Result<(), Error> {
+        self.end_command()?;
+        self.description = None;
+
+        let variables = std::mem::take(&mut self.variables);
+        for mut command in self.block.drain(..) {
+            let used = template::placeholders(&command.command);
+            command.variables = variables
+                .iter()
+                .filter(|variable| used.contains(&variable.name.as_str()))
+                .cloned()
+                .collect();
+            self.commands.push(command);
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Vec<Command>, Error> {
+        self.end_block()?;
+        Ok(self.commands)
+    }
+}
+
+/// Characters allowed in a variable name, they are the same allowed inside a `<placeholder>`.
+pub fn is_variable_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIT: &str = "\
+% git, code
+
+# Change branch
+git checkout <branch>
+
+$ branch: git branch | awk '{print $NF}' --- --column 1
+
+; A comment
+# Show the log
+git log \\
+  --oneline
+
+% docker
+
+# Logs of a container
+docker logs <container>
+";
+
+    #[test]
+    fn test_parse() {
+        let commands = parse(Path::new("git.cheat"), GIT).unwrap();
+        assert_eq!(commands.len(), 3);
+
+        assert_eq!(commands[0].command, "git checkout <branch>");
+        assert_eq!(commands[0].description, "Change branch");
+        assert_eq!(commands[0].tags, vec!["git", "code"]);
+        assert_eq!(commands[0].variables, vec![Variable {
+            name: "branch".into(),
+            generator: Some("git branch | awk '{print $NF}'".into()),
+            ..Default::default()
+        }]);
+        assert_eq!(commands[0].source.as_ref().unwrap().line, 4);
+
+        assert_eq!(commands[1].command, "git log \\\n  --oneline");
+        assert_eq!(commands[1].description, "Show the log");
+        assert!(commands[1].variables.is_empty());
+
+        assert_eq!(commands[2].command, "docker logs <container>");
+        assert_eq!(commands[2].tags, vec!["docker"]);
+        assert!(commands[2].variables.is_empty());
+    }
+
+    #[test]
+    fn test_parse_go_templates() {
+        let content = "\
+% docker
+
+# Status of a container
+docker inspect --format '{{.State.Status}}' <container>
+
+# Logs of a container
+docker logs <container>
+";
+        let commands = parse(Path::new("docker.cheat"), content).unwrap();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].command, "docker inspect --format '{{.State.Status}}' <container>");
+    }
+
+    #[test]
+    fn test_parse_types() {
+        let content = "\
+echo <env> <replicas> <tag> <file> {{folder | shellquote}}
+
+$ env: --- --choices dev,staging,prod --default dev
+$ replicas: --- --type int --required
+$ tag: git tag --- --type regex --pattern '^v[0-9]+$' --optional
+$ file: --- --type path
+$ folder: --- --type dir
+";
+        let commands = parse(Path::new("types.cheat"), content).unwrap();
+        let variables = &commands[0].variables;
+
+        assert_eq!(variables[0].kind, VariableType::Enum(vec!["dev".into(), "staging".into(), "prod".into()]));
+        assert_eq!(variables[0].default.as_deref(), Some("dev"));
+        assert!(!variables[0].required);
+        assert_eq!(variables[1].kind, VariableType::Integer);
+        assert!(variables[1].required);
+        assert_eq!(variables[2].kind, VariableType::Regex(Pattern(regex::Regex::new("^v[0-9]+$").unwrap())));
+        assert_eq!(variables[2].generator.as_deref(), Some("git tag"));
+        assert_eq!(variables[3].kind, VariableType::Path);
+        assert_eq!(variables[4].kind, VariableType::Directory);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse(Path::new("bad.cheat"), "% a\n\necho <x>\n$ x git branch\n").unwrap_err();
+        assert_eq!(err.to_string(), "bad.cheat:4: expected `$ name: command` in variable declaration");
+
+        let err = parse(Path::new("bad.cheat"), "$ a b: ls\n").unwrap_err();
+        assert_eq!(err.to_string(), "bad.cheat:1: invalid character ` ` in variable name `a b`");
+
+        let err = parse(Path::new("bad.cheat"), "$ a: ls\n$ a: ls\n").unwrap_err();
+        assert_eq!(err.to_string(), "bad.cheat:2: variable `a` declared twice");
+
+        for (options, message) in [
+            ("--type float", "unknown variable type `float`"),
+            ("--type enum", "`--type enum` needs `--choices`"),
+            ("--type int --choices a,b", "`--choices` and `--pattern` don't apply to `--type int`"),
+            ("--pattern '('", "invalid pattern `(`"),
+            ("--type int --default ten", "invalid default `ten` for `a`: must be an integer"),
+            ("--default", "missing value for `--default`"),
+            ("--default 'open", "invalid variable options: missing closing quote"),
+        ] {
+            let err = parse(Path::new("bad.cheat"), &format!("$ a: --- {}\n", options)).unwrap_err();
+            assert!(err.to_string().starts_with(&format!("bad.cheat:1: {}", message)), "{}", err);
+        }
+
+        // Template errors point to the line and column in the file.
+        let err = parse(Path::new("bad.cheat"), "% tar\n\ntar czf out.tgz \\\n  {{#if verbose}}-v <dir>\n").unwrap_err();
+        assert_eq!(err.to_string(), "bad.cheat:4:3: unclosed `{{#if verbose}}`");
+    }
+}
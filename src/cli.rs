@@ -0,0 +1,87 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use crate::error::Error;
+use crate::history::{self, History};
+use crate::widget::Widget;
+
+/// Interactive cheatsheet for the command line.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+    /// Text to search when navu opens.
+    #[arg(short, long)]
+    pub query: Option<String>,
+    /// Text to search when navu opens, like `--query`.
+    #[arg(value_name = "QUERY", conflicts_with = "query")]
+    pub words: Vec<String>,
+    /// Choose the command right away if it is the only one found.
+    #[arg(short = '1', long)]
+    pub select_1: bool,
+    /// Exit right away if no command is found.
+    #[arg(short = '0', long)]
+    pub exit_0: bool,
+    /// Write the command chosen to this file instead of stdout.
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Manage the history of selected commands, used to order the results.
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+    /// Print the key binding for a shell, to be sourced from its startup file.
+    Widget {
+        shell: Widget,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HistoryAction {
+    /// Forget every command.
+    Reset,
+    /// Forget the commands not selected in the last days.
+    Prune {
+        /// Commands selected in this number of days are kept.
+        #[arg(long, default_value_t = 90)]
+        days: u64,
+    },
+    /// Forget a value given to a variable, or all of its values.
+    Forget {
+        /// Name of the variable, shared by every command using it.
+        variable: String,
+        /// Value to forget, every value of the variable if missing.
+        value: Option<String>,
+    },
+}
+
+impl HistoryAction {
+    pub fn run(self) -> Result<(), Error> {
+        let mut history = match self {
+            // The file isn't read to reset it, so a broken one can be reset too.
+            HistoryAction::Reset => History::path().map(History::empty).unwrap_or_default(),
+            _ => History::load()?,
+        };
+        let message = match self {
+            HistoryAction::Reset => "Removed every command from the history".to_string(),
+            HistoryAction::Prune { days } => {
+                let removed = history.prune(days, history::now());
+                format!("Removed {} commands from the history, {} left", removed, history.len())
+            },
+            HistoryAction::Forget { variable, value } => {
+                let removed = history.forget(&variable, value.as_deref());
+                format!("Removed {} values of `{}` from the history", removed, variable)
+            },
+        };
+        history.save()?;
+
+        println!("{}", message);
+        Ok(())
+    }
+}
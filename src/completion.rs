@@ -0,0 +1,210 @@
+//! Completion of paths typed in the inputs of the path variables.
+
+use std::fs;
+use std::path::PathBuf;
+
+use nucleo::pattern::{CaseMatching, Normalization, Pattern};
+use nucleo::{Config, Matcher, Utf32Str};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::event::Event;
+
+/// Entries listed for a folder at most, to keep huge folders responsive.
+const MAX_ENTRIES: usize = 2000;
+
+/// Which entries are offered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathOptions {
+    /// Entries starting with a dot, they are offered anyway once a dot is typed.
+    pub hidden: bool,
+    pub directories_only: bool,
+}
+
+/// Path typed in a field, whose folder is listed in the background.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listing {
+    pub variable: String,
+    pub text: String,
+    pub options: PathOptions,
+}
+
+/// Entries found for a listing, sent as an [`Event::Listed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listed {
+    pub listing: Listing,
+    pub entries: Vec<String>,
+}
+
+/// Lists the folder in the blocking threads of the runtime, so a slow file system doesn't freeze the interface.
+pub fn list(listing: Listing, sender: UnboundedSender<Event>) {
+    tokio::task::spawn_blocking(move || {
+        let entries = entries(&listing.text, listing.options);
+        let _ = sender.send(Event::Listed(Listed { listing, entries }));
+    });
+}
+
+/// Replaces a leading `~` with the home directory.
+pub fn expand_tilde(path: &str) -> PathBuf {
+    let home = || dirs::home_dir().unwrap_or_default();
+
+    match path.strip_prefix('~') {
+        Some("") => home(),
+        Some(rest) if rest.starts_with('/') => home().join(&rest[1..]),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Splits the text in the folder part, up to the last `/`, and the start of the name being typed.
+pub fn split(text: &str) -> (&str, &str) {
+    match text.rfind('/') {
+        Some(slash) => text.split_at(slash + 1),
+        None if text == "~" => (text, ""),
+        None => ("", text),
+    }
+}
+
+/// Entries of the folder being typed whose name matches the one typed so far, spelled like the text, folders
+/// end with `/`.
+///
+/// Relative paths are resolved from the current directory.
+pub fn entries(text: &str, options: PathOptions) -> Vec<String> {
+    let (folder, name) = split(text);
+    let folder = if folder == "~" { "~/" } else { folder };
+    let path = match folder {
+        "" => PathBuf::from("."),
+        folder => expand_tilde(folder),
+    };
+
+    let Ok(read) = fs::read_dir(&path) else {
+        return vec![];
+    };
+
+    // Names are matched as the suggestions are, and before the cap so the ones wanted are never left out.
+    let pattern = Pattern::parse(name, CaseMatching::Smart, Normalization::Smart);
+    let mut matcher = Matcher::new(Config::DEFAULT.match_paths());
+    let mut buffer = vec![];
+    let mut names = read
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|file_name| !file_name.starts_with('.') || options.hidden || name.starts_with('.'))
+        .filter(|file_name| pattern.score(Utf32Str::new(file_name, &mut buffer), &mut matcher).is_some())
+        .collect::<Vec<_>>();
+    names.sort();
+
+    let mut entries = names
+        .into_iter()
+        .filter_map(|file_name| {
+            // Symbolic links to folders are folders too.
+            let is_dir = fs::metadata(path.join(&file_name)).is_ok_and(|metadata| metadata.is_dir());
+            if options.directories_only && !is_dir {
+                return None;
+            }
+
+            Some(format!("{}{}{}", folder, file_name, if is_dir { "/" } else { "" }))
+        })
+        .take(MAX_ENTRIES)
+        .collect::<Vec<_>>();
+
+    entries.sort();
+    entries
+}
+
+/// Text completed up to the longest prefix shared by the entries starting with it, `None` if that adds nothing.
+pub fn complete(text: &str, options: PathOptions) -> Option<String> {
+    // `~` alone is the home directory.
+    if text == "~" {
+        return Some("~/".to_string());
+    }
+
+    let candidates = entries(text, options)
+        .into_iter()
+        .filter(|entry| entry.starts_with(text))
+        .collect::<Vec<_>>();
+
+    let first = candidates.first()?;
+    let common = candidates.iter().fold(first.as_str(), |common, entry| {
+        let length = common
+            .char_indices()
+            .zip(entry.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(common.len().min(entry.len()), |((index, _), _)| index);
+        &common[..length]
+    });
+
+    (common.len() > text.len()).then(|| common.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("navu-completion-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for folder in ["src/ui", "scripts", ".git"] {
+            fs::create_dir_all(root.join(folder)).unwrap();
+        }
+        for file in ["src/main.rs", "src/matcher.rs", "README.md", ".env"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn test_entries() {
+        let root = tree();
+        let root = format!("{}/", root.display());
+        let options = PathOptions::default();
+
+        assert_eq!(entries(&root, options), vec![
+            format!("{}README.md", root),
+            format!("{}scripts/", root),
+            format!("{}src/", root),
+        ]);
+        assert_eq!(entries(&format!("{}.", root), options), vec![format!("{}.env", root), format!("{}.git/", root), format!("{}README.md", root)]);
+        assert_eq!(entries(&format!("{}src/mn", root), options), vec![format!("{}src/main.rs", root)]);
+        assert_eq!(entries(&root, PathOptions { hidden: true, directories_only: true }), vec![
+            format!("{}.git/", root),
+            format!("{}scripts/", root),
+            format!("{}src/", root),
+        ]);
+
+        assert_eq!(complete(&format!("{}sr", root), options), Some(format!("{}src/", root)));
+        assert_eq!(complete(&format!("{}src/m", root), options), Some(format!("{}src/ma", root)));
+        assert_eq!(complete(&format!("{}s", root), options), None);
+        assert_eq!(complete(&format!("{}nothing", root), options), None);
+        assert_eq!(complete("~", options), Some("~/".into()));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_huge_folder() {
+        let root = std::env::temp_dir().join(format!("navu-completion-huge-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        for index in 0..=MAX_ENTRIES {
+            fs::write(root.join(format!("file-{:04}", index)), "").unwrap();
+        }
+        fs::write(root.join("wanted"), "").unwrap();
+        let root = format!("{}/", root.display());
+
+        assert_eq!(entries(&root, PathOptions::default()).len(), MAX_ENTRIES);
+        assert_eq!(entries(&format!("{}wan", root), PathOptions::default()), vec![format!("{}wanted", root)]);
+        assert_eq!(complete(&format!("{}w", root), PathOptions::default()), Some(format!("{}wanted", root)));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_tilde() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_tilde("~"), home);
+        assert_eq!(expand_tilde("~/src"), home.join("src"));
+        assert_eq!(expand_tilde("~user/src"), PathBuf::from("~user/src"));
+        assert_eq!(expand_tilde("/tmp"), PathBuf::from("/tmp"));
+
+        assert_eq!(split("~"), ("~", ""));
+        assert_eq!(split("~/sr"), ("~/", "sr"));
+        assert_eq!(split("src"), ("", "src"));
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::cheat;
+use crate::danger::Rule;
+use crate::error::Error;
+use crate::fuzzy::Phonetic;
+use crate::shell::Shell;
+
+/// User configuration, read from `$NAVU_CONFIG` or `navu/config.toml` under the XDG config directory.
+///
+/// Every setting is optional, a missing file is the same as an empty one.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub search: SearchConfig,
+    pub variables: VariablesConfig,
+    pub danger: DangerConfig,
+    /// Shell the values are quoted for, the one in `$SHELL` if not set.
+    pub shell: Option<Shell>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SearchConfig {
+    /// Algorithm used to find commands that sound like the query.
+    pub phonetic: Phonetic,
+    /// Below this number of fuzzy matches, phonetic matches are added to the results.
+    pub phonetic_min_results: usize,
+    /// Algorithm for the commands of some folders, relative to the cheats folder, like `de = "cologne"`.
+    pub catalogs: BTreeMap<PathBuf, Phonetic>,
+    /// Guess the language of each description and use the algorithm made for it.
+    pub detect_language: bool,
+    /// Weight of the fuzzy score when ordering the results.
+    pub fuzzy_weight: f64,
+    /// Weight of how often and how recently a command was selected when ordering the results.
+    pub frecency_weight: f64,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            phonetic: Phonetic::default(),
+            phonetic_min_results: 5,
+            catalogs: BTreeMap::new(),
+            detect_language: false,
+            fuzzy_weight: 1.0,
+            frecency_weight: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct VariablesConfig {
+    /// Seconds a generator can run before it is killed.
+    pub generator_timeout: u64,
+}
+
+impl Default for VariablesConfig {
+    fn default() -> Self {
+        Self { generator_timeout: 10 }
+    }
+}
+
+/// Commands to confirm before they are printed or run, see [`crate::danger`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct DangerConfig {
+    /// Check the built-in rules, like `rm -rf /` or `git push --force`, besides the ones given.
+    pub builtin: bool,
+    /// Cheats with any of these tags are always confirmed.
+    pub tags: Vec<String>,
+    pub rules: Vec<Rule>,
+}
+
+impl Default for DangerConfig {
+    fn default() -> Self {
+        Self { builtin: true, tags: vec!["danger".into()], rules: vec![] }
+    }
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        std::env::var_os("NAVU_CONFIG")
+            .map(PathBuf::from)
+            .or_else(|| dirs::config_dir().map(|config| config.join("navu").join("config.toml")))
+    }
+
+    pub fn load() -> Result<Self, Error> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut config: Self = toml::from_str(&content).map_err(|err| Error::Config {
+            path,
+            message: err.to_string(),
+        })?;
+        config.search.resolve_catalogs(&cheat::default_path());
+
+        Ok(config)
+    }
+
+    /// Shell configured, or the one in `$SHELL`.
+    pub fn shell(&self) -> Shell {
+        self.shell.unwrap_or_else(Shell::detect)
+    }
+}
+
+impl SearchConfig {
+    /// Makes the catalog folders absolute, relative ones are inside `root`.
+    fn resolve_catalogs(&mut self, root: &Path) {
+        self.catalogs = std::mem::take(&mut self.catalogs)
+            .into_iter()
+            .map(|(folder, phonetic)| (root.join(folder), phonetic))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config: Config = toml::from_str("[search]\nphonetic = \"nysiis\"\n").unwrap();
+        assert_eq!(config.search.phonetic, Phonetic::Nysiis);
+        assert_eq!(config.search.phonetic_min_results, 5);
+
+        assert!(toml::from_str::<Config>("[search]\nphonetic = \"caverphone\"\n").is_err());
+
+        let config: Config = toml::from_str("shell = \"pwsh\"\n").unwrap();
+        assert_eq!(config.shell(), Shell::PowerShell);
+    }
+
+    #[test]
+    fn test_catalogs() {
+        let mut config: Config = toml::from_str(
+            "[search]\ndetect-language = true\n[search.catalogs]\nde = \"cologne\"\n\"/srv/es\" = \"spanish-soundex\"\n",
+        )
+        .unwrap();
+        assert!(config.search.detect_language);
+
+        config.search.resolve_catalogs(Path::new("/cheats"));
+        assert_eq!(
+            config.search.catalogs.into_iter().collect::<Vec<_>>(),
+            vec![
+                (PathBuf::from("/cheats/de"), Phonetic::Cologne),
+                (PathBuf::from("/srv/es"), Phonetic::SpanishSoundex),
+            ]
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Commands that can destroy data or history, checked before they are printed or run.
+//!
+//! Each rule is a regular expression matched against every simple command of the syntax tree, unless
+//! the same simple command matches its `unless` expression, like `kubectl delete --dry-run=client`.
+
+use std::ops::Range;
+
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+
+use crate::config::DangerConfig;
+use crate::syntax;
+
+/// Built-in rules, as name, pattern and the pattern that makes them safe.
+const BUILTIN: [(&str, &str, Option<&str>); 6] = [
+    (
+        "recursive removal of the root or home folder",
+        r#"\brm\s+(?:-\S+\s+)*(?:-[a-zA-Z]*[rR][a-zA-Z]*|--recursive)\s+(?:-\S+\s+)*["']?(?:/\*?|~/?|\$HOME/?)["']?(?:\s|$)"#,
+        None,
+    ),
+    ("write to a device", r"\bdd\s.*?\bof=/dev/\S*", None),
+    ("format a file system", r"\bmkfs(?:\.\w+)?\b", None),
+    ("force push", r"\bgit\s+push\b(?:\s+\S+)*?\s+(?:--force|-f)(?:\s|$)", None),
+    ("drop a table or database", r"(?i)\bdrop\s+(?:table|database|schema)\b", None),
+    (
+        "delete Kubernetes resources",
+        r"\bkubectl\s+(?:\S+\s+)*?delete(?:\s|$)",
+        Some(r"\s--dry-run(?:=(?:client|server)|\s|$)"),
+    ),
+];
+
+/// Rule telling dangerous commands apart.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    /// What the command does, shown to confirm it.
+    pub name: String,
+    /// Risky part of the command.
+    #[serde(deserialize_with = "regex")]
+    pub pattern: Regex,
+    /// The command is safe after all if it matches this too.
+    #[serde(default, deserialize_with = "optional_regex")]
+    pub unless: Option<Regex>,
+}
+
+fn regex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+    let pattern = String::deserialize(deserializer)?;
+    Regex::new(&pattern).map_err(serde::de::Error::custom)
+}
+
+fn optional_regex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Regex>, D::Error> {
+    regex(deserializer).map(Some)
+}
+
+/// Reason to confirm a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Risk {
+    /// Name of the rule, or the tag of the cheat.
+    pub reason: String,
+    /// Risky part of the command, as a byte range, none for tagged cheats.
+    pub range: Option<Range<usize>>,
+}
+
+/// Rules commands are checked with.
+#[derive(Debug, Clone)]
+pub struct Danger {
+    rules: Vec<Rule>,
+    tags: Vec<String>,
+}
+
+impl Default for Danger {
+    fn default() -> Self {
+        Self::new(&DangerConfig::default())
+    }
+}
+
+impl Danger {
+    pub fn new(config: &DangerConfig) -> Self {
+        let builtin = BUILTIN.iter().filter(|_| config.builtin).map(|(name, pattern, unless)| Rule {
+            name: name.to_string(),
+            pattern: Regex::new(pattern).expect("built-in rules are valid"),
+            unless: unless.map(|unless| Regex::new(unless).expect("built-in rules are valid")),
+        });
+
+        Self { rules: builtin.chain(config.rules.iter().cloned()).collect(), tags: config.tags.clone() }
+    }
+
+    /// Risks of the command, from a cheat with the tags given, in the order they appear.
+    pub fn check(&self, command: &str, tags: &[String]) -> Vec<Risk> {
+        let mut risks: Vec<Risk> = tags
+            .iter()
+            .filter(|tag| self.tags.contains(tag))
+            .map(|tag| Risk { reason: format!("tagged {}", tag), range: None })
+            .collect();
+
+        for segment in segments(command) {
+            let text = &command[segment.clone()];
+            for rule in &self.rules {
+                if rule.unless.as_ref().is_some_and(|unless| unless.is_match(text)) {
+                    continue;
+                }
+                for found in rule.pattern.find_iter(text) {
+                    // Patterns end at a space or the end, the space isn't part of the risk.
+                    let start = segment.start + found.start();
+                    let risk = Risk { reason: rule.name.clone(), range: Some(start..start + found.as_str().trim_end().len()) };
+                    // Substitutions are checked on their own and with the command around them.
+                    if !risks.contains(&risk) {
+                        risks.push(risk);
+                    }
+                }
+            }
+        }
+
+        risks.sort_by_key(|risk| risk.range.as_ref().map(|range| range.start));
+        risks
+    }
+}
+
+/// Byte ranges of the simple commands, the ones in compound commands and substitutions too, and the whole
+/// command if it can't be parsed, so that nothing is left unchecked.
+fn segments(command: &str) -> Vec<Range<usize>> {
+    let parsed = syntax::parse(command);
+    let mut segments: Vec<_> = parsed.list.simple_commands().into_iter().map(|simple| simple.span.clone()).collect();
+    if !parsed.diagnostics.is_empty() {
+        segments.push(0..command.len());
+    }
+    segments.retain(|segment| !command[segment.clone()].trim().is_empty());
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Risky parts of the command, with the built-in rules.
+    fn risky(command: &str) -> Vec<&str> {
+        Danger::default()
+            .check(command, &[])
+            .into_iter()
+            .filter_map(|risk| risk.range)
+            .map(|range| &command[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_builtin() {
+        for (command, expected) in [
+            ("rm -rf /", vec!["rm -rf /"]),
+            ("sudo rm -fr --no-preserve-root / ", vec!["rm -fr --no-preserve-root /"]),
+            ("rm -r -f ~/", vec!["rm -r -f ~/"]),
+            ("rm --recursive \"$HOME\"", vec!["rm --recursive \"$HOME\""]),
+            ("rm -rf /tmp/build", vec![]),
+            ("rm -f /", vec![]),
+            ("dd if=disk.img of=/dev/sdb bs=4M", vec!["dd if=disk.img of=/dev/sdb"]),
+            ("dd if=/dev/zero of=disk.img", vec![]),
+            ("sudo mkfs.ext4 /dev/sdb1", vec!["mkfs.ext4"]),
+            ("git push --force origin main", vec!["git push --force"]),
+            ("git push -f", vec!["git push -f"]),
+            ("git push --force-with-lease", vec![]),
+            ("psql -c 'drop table users'", vec!["drop table"]),
+            ("kubectl -n prod delete pod web", vec!["kubectl -n prod delete"]),
+            ("kubectl delete pod web --dry-run=client", vec![]),
+            ("kubectl delete pod web --dry-run=none", vec!["kubectl delete"]),
+            ("kubectl get pods | grep delete", vec![]),
+            ("kubectl delete ns a --dry-run=server && kubectl delete ns b", vec!["kubectl delete"]),
+            ("git push origin my-fix", vec![]),
+            ("ls -la; mkfs /dev/sdc", vec!["mkfs"]),
+            ("echo $(rm -rf /) `mkfs x`", vec!["rm -rf /", "mkfs"]),
+            ("cat <<EOF\nrm -rf /\nEOF", vec![]),
+            ("if true; then git push -f; fi", vec!["git push -f"]),
+        ] {
+            assert_eq!(risky(command), expected, "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_config() {
+        let config: DangerConfig = toml::from_str(
+            "builtin = false\ntags = [\"prod\"]\n[[rules]]\nname = \"destroy\"\npattern = 'terraform\\s+destroy'\nunless = '-target'\n",
+        )
+        .unwrap();
+        let danger = Danger::new(&config);
+
+        assert_eq!(
+            danger.check("terraform destroy", &["prod".into(), "danger".into()]),
+            vec![
+                Risk { reason: "tagged prod".into(), range: None },
+                Risk { reason: "destroy".into(), range: Some(0..17) },
+            ]
+        );
+        assert!(danger.check("terraform destroy -target=x; rm -rf /", &[]).is_empty());
+
+        assert!(toml::from_str::<DangerConfig>("[[rules]]\nname = \"bad\"\npattern = '('\n").is_err());
+    }
+
+    #[test]
+    fn test_tags() {
+        let risks = Danger::default().check("reboot", &["system".into(), "danger".into()]);
+        assert_eq!(risks, vec![Risk { reason: "tagged danger".into(), range: None }]);
+    }
+
+    #[test]
+    fn test_segments() {
+        let command = "a 'b;c' \"d|e\" f\\;g; h && (i) | j\nk";
+        let segments: Vec<_> = segments(command).into_iter().map(|range| command[range].trim()).collect();
+        assert_eq!(segments, ["a 'b;c' \"d|e\" f\\;g", "h", "i", "j", "k"]);
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use thiserror::Error;
 
 /// TODO: Add Errors for the application, such as: File not found, error reading file, search thread down, etc.
@@ -5,6 +7,38 @@ use thiserror::Error;
 pub enum Error {
     #[error("Unknown Error: {0}")]
     Unknown(#[from] std::io::Error),
+    #[error("{}:{line}: {message}", path.display())]
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    #[error("{}:{line}:{column}: {message}", path.display())]
+    Template {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("Invalid configuration in {}: {message}", path.display())]
+    Config {
+        path: PathBuf,
+        message: String,
+    },
+    #[error("Invalid history in {}: {message}", path.display())]
+    History {
+        path: PathBuf,
+        message: String,
+    },
+    #[error("column {column}: {message}")]
+    Query {
+        column: usize,
+        message: String,
+    },
+    #[error("Invalid ignore rules: {0}")]
+    Ignore(#[from] ignore::Error),
+    #[error("Could not open a terminal: {0}")]
+    Terminal(String),
     #[error("Error: {0}")]
     Custom(String),
-}
\ No newline at end of file
+}
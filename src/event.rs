@@ -5,9 +5,15 @@ use futures::{FutureExt, StreamExt};
 use tokio::sync::mpsc;
 
 use crate::app::AppResult;
+use crate::completion::Listed;
+use crate::error::Error;
+use crate::execution::Executed;
+use crate::generator::Generated;
+use crate::pty::PtyEvent;
+use crate::search::Hit;
 
 /// Terminal events.
-#[derive(Clone, Copy, Debug)]
+#[derive(Debug)]
 pub enum Event {
     /// Terminal tick.
     Tick,
@@ -17,6 +23,18 @@ pub enum Event {
     Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
+    /// The catalog finished loading, with the errors found on the way.
+    Loaded(Vec<Error>),
+    /// A generator of a variable finished.
+    Generated(Generated),
+    /// The entries of a path typed in the form were listed.
+    Listed(Listed),
+    /// Fuzzy results once the catalog is loaded and matched, at most the first two.
+    Settled(Vec<Hit>),
+    /// Output or exit of the command executed.
+    Executed(Executed),
+    /// Output or exit of the command in the terminal pane.
+    Pty(PtyEvent),
 }
 
 /// Terminal event handler.
@@ -63,13 +81,8 @@ impl EventHandler {
                       CrosstermEvent::Resize(x, y) => {
                         _sender.send(Event::Resize(x, y)).unwrap();
                       },
-                      CrosstermEvent::FocusLost => {
-                        println!("Focus lost");
-                      },
-                      CrosstermEvent::FocusGained => {
-                        println!("Focus gained");
-                      },
-                      CrosstermEvent::Paste(_) => {
+                      // Ignored, and never printed: stdout carries the command chosen.
+                      CrosstermEvent::FocusLost | CrosstermEvent::FocusGained | CrosstermEvent::Paste(_) => {
                       },
                     }
                   }
@@ -83,6 +96,11 @@ impl EventHandler {
         }
     }
 
+    /// Sender to publish application events, such as the ones of background tasks.
+    pub fn sender(&self) -> mpsc::UnboundedSender<Event> {
+        self.sender.clone()
+    }
+
     /// Receive the next event from the handler thread.
     ///
     /// This function will always block the current thread if
@@ -91,9 +109,6 @@ impl EventHandler {
         self.receiver
             .recv()
             .await
-            .ok_or(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "This is an IO error",
-            )))
+            .ok_or(Box::new(std::io::Error::other("This is an IO error")))
     }
 }
@@ -0,0 +1,205 @@
+//! Execution of the chosen command through the shell, its output streamed to the application line by line.
+
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::event::Event;
+use crate::shell::Shell;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Progress of an execution, sent as an [`Event::Executed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Executed {
+    /// Identifier of the [`Execution`], to tell apart outdated runs.
+    pub id: u64,
+    pub progress: Progress,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Progress {
+    /// Line written by the command, without the line break.
+    Line { text: String, stderr: bool },
+    /// The command finished, or couldn't be started.
+    Exited { status: String, success: bool, elapsed: Duration },
+}
+
+/// Command running in the background of the tokio runtime, it is killed when the handle is dropped.
+#[derive(Debug)]
+pub struct Execution {
+    pub id: u64,
+    group: ProcessGroup,
+}
+
+/// Process group led by a command spawned with `process_group(0)`, so the processes it starts are killed with
+/// it. Dropping it kills the group, unless the command exited.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessGroup {
+    leader: Option<libc::pid_t>,
+    /// Set once the leader is waited for, its identifier can be given to another process after that.
+    exited: Arc<AtomicBool>,
+}
+
+impl ProcessGroup {
+    pub fn of(child: &Child) -> Self {
+        Self { leader: child.id().map(|pid| pid as libc::pid_t), exited: Arc::default() }
+    }
+
+    /// The command exited, the group is no longer killed.
+    pub fn exited(&self) {
+        self.exited.store(true, Ordering::Release);
+    }
+
+    /// Kills the command and everything it started, unless it exited.
+    pub fn kill(&self) {
+        if let Some(leader) = self.leader.filter(|_| !self.exited.load(Ordering::Acquire)) {
+            // SAFETY: `kill` has no memory effects, the group is the one the command leads.
+            unsafe {
+                libc::kill(-leader, libc::SIGKILL);
+            }
+        }
+    }
+}
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+impl Execution {
+    /// Runs the command with the shell, the output and the exit are sent as events.
+    pub fn spawn(command: &str, shell: Shell, sender: UnboundedSender<Event>) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let report = move |progress| {
+            let _ = sender.send(Event::Executed(Executed { id, progress }));
+        };
+
+        let started = Instant::now();
+        let child = Command::new(shell.program())
+            .arg(shell.command_flag())
+            .arg(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .kill_on_drop(true)
+            .spawn();
+
+        let child = match child {
+            Ok(child) => child,
+            Err(err) => {
+                report(Progress::Exited { status: err.to_string(), success: false, elapsed: started.elapsed() });
+                return Self { id, group: ProcessGroup::default() };
+            },
+        };
+
+        let group = ProcessGroup::of(&child);
+        tokio::spawn(execute(child, started, group.clone(), report));
+        Self { id, group }
+    }
+
+    /// Kills the command and everything it started, its exit is still sent.
+    pub fn kill(&self) {
+        self.group.kill();
+    }
+}
+
+/// Reads a line, keeping the bytes read so far if the future is dropped. `None` at the end of the output.
+async fn read_line(reader: &mut BufReader<impl AsyncRead + Unpin>, buffer: &mut Vec<u8>) -> Option<String> {
+    match reader.read_until(b'\n', buffer).await {
+        Ok(0) | Err(_) if buffer.is_empty() => None,
+        _ => {
+            let line = String::from_utf8_lossy(buffer).trim_end_matches(['\n', '\r']).to_string();
+            buffer.clear();
+            Some(line)
+        },
+    }
+}
+
+/// Streams the output of the child until it exits, reporting its progress.
+async fn execute(mut child: Child, started: Instant, group: ProcessGroup, report: impl Fn(Progress)) {
+    let (mut stdout, mut stderr) = match (child.stdout.take(), child.stderr.take()) {
+        (Some(stdout), Some(stderr)) => (BufReader::new(stdout), BufReader::new(stderr)),
+        _ => unreachable!("stdout and stderr are piped"),
+    };
+    let (mut stdout_buffer, mut stderr_buffer) = (vec![], vec![]);
+    let (mut stdout_open, mut stderr_open) = (true, true);
+
+    while stdout_open || stderr_open {
+        tokio::select! {
+            line = read_line(&mut stdout, &mut stdout_buffer), if stdout_open => match line {
+                Some(text) => report(Progress::Line { text, stderr: false }),
+                None => stdout_open = false,
+            },
+            line = read_line(&mut stderr, &mut stderr_buffer), if stderr_open => match line {
+                Some(text) => report(Progress::Line { text, stderr: true }),
+                None => stderr_open = false,
+            },
+        }
+    }
+
+    let (status, success) = match child.wait().await {
+        Ok(status) => (status.to_string(), status.success()),
+        Err(err) => (err.to_string(), false),
+    };
+    group.exited();
+    report(Progress::Exited { status, success, elapsed: started.elapsed() });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn progress(command: &str, kill_after: Option<Duration>) -> Vec<Progress> {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let execution = Execution::spawn(command, Shell::Sh, sender);
+        if let Some(delay) = kill_after {
+            tokio::time::sleep(delay).await;
+            execution.kill();
+        }
+
+        let mut progress = vec![];
+        while let Some(Event::Executed(executed)) = receiver.recv().await {
+            assert_eq!(executed.id, execution.id);
+            let exited = matches!(executed.progress, Progress::Exited { .. });
+            progress.push(executed.progress);
+            if exited {
+                break;
+            }
+        }
+        progress
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let progress = progress("echo one; echo two >&2; printf 'three'; exit 3", None).await;
+        let (lines, exit) = progress.split_at(progress.len() - 1);
+
+        let mut stdout = lines.iter().filter_map(|line| match line {
+            Progress::Line { text, stderr: false } => Some(text.as_str()),
+            _ => None,
+        });
+        assert_eq!(stdout.next(), Some("one"));
+        assert_eq!(stdout.next(), Some("three"));
+        assert!(lines.contains(&Progress::Line { text: "two".into(), stderr: true }));
+        assert!(matches!(&exit[0], Progress::Exited { status, success: false, .. } if status == "exit status: 3"));
+    }
+
+    #[tokio::test]
+    async fn test_kill() {
+        let progress = progress("echo started; sleep 10", Some(Duration::from_millis(200))).await;
+        assert_eq!(progress[0], Progress::Line { text: "started".into(), stderr: false });
+        let Progress::Exited { status, success, elapsed } = &progress[1] else {
+            panic!("expected the exit, got {:?}", progress);
+        };
+        assert!(!success && status.contains("signal"), "{}", status);
+        assert!(*elapsed < Duration::from_secs(5));
+    }
+}
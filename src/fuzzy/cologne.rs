@@ -0,0 +1,89 @@
+use crate::fuzzy::{letters, PhoneticEncoder};
+
+/// Kölner Phonetik, Soundex tuned for German: every letter becomes a digit and there is no length limit.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Cologne;
+
+impl PhoneticEncoder for Cologne {
+    fn encode(&self, word: &str) -> Vec<String> {
+        let word = letters(word);
+        let mut digits = String::with_capacity(word.len() + 1);
+
+        for (n, &letter) in word.iter().enumerate() {
+            let previous = n.checked_sub(1).map(|n| word[n]);
+            let next = word.get(n + 1).copied();
+
+            let digit = match letter {
+                'A' | 'E' | 'I' | 'J' | 'O' | 'U' | 'Y' => "0",
+                'H' => "",
+                'B' => "1",
+                'P' if next == Some('H') => "3",
+                'P' => "1",
+                'D' | 'T' if matches!(next, Some('C' | 'S' | 'Z')) => "8",
+                'D' | 'T' => "2",
+                'F' | 'V' | 'W' => "3",
+                'G' | 'K' | 'Q' => "4",
+                'C' if n == 0 && matches!(next, Some('A' | 'H' | 'K' | 'L' | 'O' | 'Q' | 'R' | 'U' | 'X')) => "4",
+                'C' if n == 0 => "8",
+                'C' if matches!(previous, Some('S' | 'Z')) => "8",
+                'C' if matches!(next, Some('A' | 'H' | 'K' | 'O' | 'Q' | 'U' | 'X')) => "4",
+                'C' => "8",
+                'X' if matches!(previous, Some('C' | 'K' | 'Q')) => "8",
+                'X' => "48",
+                'L' => "5",
+                'M' | 'N' => "6",
+                'R' => "7",
+                'S' | 'Z' => "8",
+                _ => "",
+            };
+
+            // Repeated digits count once, even across an H.
+            for digit in digit.chars() {
+                if !digits.ends_with(digit) {
+                    digits.push(digit);
+                }
+            }
+        }
+
+        if digits.is_empty() {
+            return vec![];
+        }
+
+        // Vowels only count at the start.
+        let code = digits
+            .char_indices()
+            .filter(|&(n, digit)| n == 0 || digit != '0')
+            .map(|(_, digit)| digit)
+            .collect();
+
+        vec![code]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cologne() {
+        for (word, code) in [
+            ("Müller-Lüdenscheidt", "65752682"),
+            ("Wikipedia", "3412"),
+            ("Breschnew", "17863"),
+            ("Meier", "67"),
+            ("Mayr", "67"),
+            ("Schmidt", "862"),
+            ("Schmitt", "862"),
+            ("Ahrens", "0768"),
+            ("Christoph", "47823"),
+            ("Straße", "8278"),
+            ("Strasse", "8278"),
+            ("Hexe", "048"),
+        ] {
+            assert_eq!(Cologne.encode(word), vec![code], "{}", word);
+        }
+
+        assert!(Cologne.encode("").is_empty());
+        assert!(Cologne.encode("h").is_empty());
+    }
+}
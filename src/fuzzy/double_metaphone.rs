@@ -0,0 +1,647 @@
+use crate::fuzzy::{letters, PhoneticEncoder};
+
+/// Lawrence Philips' Double Metaphone, gives a primary code and an alternative one for
+/// words whose pronunciation depends on their origin (Germanic, Slavic, Spanish, Italian...).
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DoubleMetaphone {
+    pub(crate) max_length: usize,
+}
+
+impl Default for DoubleMetaphone {
+    fn default() -> Self {
+        Self { max_length: 4 }
+    }
+}
+
+impl PhoneticEncoder for DoubleMetaphone {
+    fn encode(&self, word: &str) -> Vec<String> {
+        let word = letters(word);
+        if word.is_empty() {
+            return vec![];
+        }
+
+        let (primary, alternate) = Encoder::new(word, self.max_length).run();
+        if primary == alternate {
+            vec![primary]
+        } else {
+            vec![primary, alternate]
+        }
+    }
+}
+
+struct Encoder {
+    word: Vec<char>,
+    primary: String,
+    alternate: String,
+    max_length: usize,
+    slavo_germanic: bool,
+}
+
+impl Encoder {
+    fn new(word: Vec<char>, max_length: usize) -> Self {
+        let text = word.iter().collect::<String>();
+        let slavo_germanic = text.contains('W') || text.contains('K') || text.contains("CZ") || text.contains("WITZ");
+
+        Self {
+            word,
+            primary: String::with_capacity(max_length),
+            alternate: String::with_capacity(max_length),
+            max_length,
+            slavo_germanic,
+        }
+    }
+
+    /// Letter at `index`, `None` outside of the word.
+    fn at(&self, index: isize) -> Option<char> {
+        usize::try_from(index).ok().and_then(|index| self.word.get(index).copied())
+    }
+
+    fn is(&self, index: isize, letter: char) -> bool {
+        self.at(index) == Some(letter)
+    }
+
+    fn vowel(&self, index: isize) -> bool {
+        matches!(self.at(index), Some('A' | 'E' | 'I' | 'O' | 'U' | 'Y'))
+    }
+
+    /// Whether any of the patterns, all of the same length, is found at `index`.
+    fn contains(&self, index: isize, patterns: &[&str]) -> bool {
+        let Some(length) = patterns.first().map(|pattern| pattern.len()) else {
+            return false;
+        };
+
+        let Ok(start) = usize::try_from(index) else {
+            return false;
+        };
+
+        match self.word.get(start..start + length) {
+            Some(slice) => patterns
+                .iter()
+                .any(|pattern| pattern.chars().eq(slice.iter().copied())),
+            None => false,
+        }
+    }
+
+    fn last(&self) -> isize {
+        self.word.len() as isize - 1
+    }
+
+    fn add(&mut self, primary: &str, alternate: &str) {
+        for (code, text) in [(&mut self.primary, primary), (&mut self.alternate, alternate)] {
+            let room = self.max_length.saturating_sub(code.len());
+            code.extend(text.chars().take(room));
+        }
+    }
+
+    fn both(&mut self, code: &str) {
+        self.add(code, code);
+    }
+
+    fn complete(&self) -> bool {
+        self.primary.len() >= self.max_length && self.alternate.len() >= self.max_length
+    }
+
+    fn run(mut self) -> (String, String) {
+        let mut index: isize = if self.contains(0, &["GN", "KN", "PN", "WR", "PS"]) { 1 } else { 0 };
+
+        while !self.complete() && index <= self.last() {
+            index = match self.word[index as usize] {
+                'A' | 'E' | 'I' | 'O' | 'U' | 'Y' => {
+                    if index == 0 {
+                        self.both("A");
+                    }
+                    index + 1
+                },
+                'B' => {
+                    self.both("P");
+                    if self.is(index + 1, 'B') { index + 2 } else { index + 1 }
+                },
+                'C' => self.c(index),
+                'D' => self.d(index),
+                'F' => {
+                    self.both("F");
+                    if self.is(index + 1, 'F') { index + 2 } else { index + 1 }
+                },
+                'G' => self.g(index),
+                'H' => self.h(index),
+                'J' => self.j(index),
+                'K' => {
+                    self.both("K");
+                    if self.is(index + 1, 'K') { index + 2 } else { index + 1 }
+                },
+                'L' => self.l(index),
+                'M' => {
+                    self.both("M");
+                    if self.m_doubled(index) { index + 2 } else { index + 1 }
+                },
+                'N' => {
+                    self.both("N");
+                    if self.is(index + 1, 'N') { index + 2 } else { index + 1 }
+                },
+                'P' => self.p(index),
+                'Q' => {
+                    self.both("K");
+                    if self.is(index + 1, 'Q') { index + 2 } else { index + 1 }
+                },
+                'R' => self.r(index),
+                'S' => self.s(index),
+                'T' => self.t(index),
+                'V' => {
+                    self.both("F");
+                    if self.is(index + 1, 'V') { index + 2 } else { index + 1 }
+                },
+                'W' => self.w(index),
+                'X' => self.x(index),
+                'Z' => self.z(index),
+                _ => index + 1,
+            };
+        }
+
+        (self.primary, self.alternate)
+    }
+
+    fn germanic(&self) -> bool {
+        self.contains(0, &["VAN ", "VON "]) || self.contains(0, &["SCH"])
+    }
+
+    fn c(&mut self, index: isize) -> isize {
+        if self.c_as_k(index) {
+            self.both("K");
+            index + 2
+        } else if index == 0 && self.contains(index, &["CAESAR"]) {
+            self.both("S");
+            index + 2
+        } else if self.contains(index, &["CH"]) {
+            self.ch(index)
+        } else if self.contains(index, &["CZ"]) && !self.contains(index - 2, &["WICZ"]) {
+            // "czerny"
+            self.add("S", "X");
+            index + 2
+        } else if self.contains(index + 1, &["CIA"]) {
+            // "focaccia"
+            self.both("X");
+            index + 3
+        } else if self.contains(index, &["CC"]) && !(index == 1 && self.is(0, 'M')) {
+            self.cc(index)
+        } else if self.contains(index, &["CK", "CG", "CQ"]) {
+            self.both("K");
+            index + 2
+        } else if self.contains(index, &["CI", "CE", "CY"]) {
+            // Italian vs. English
+            if self.contains(index, &["CIO", "CIE", "CIA"]) {
+                self.add("S", "X");
+            } else {
+                self.both("S");
+            }
+            index + 2
+        } else {
+            self.both("K");
+            if self.contains(index + 1, &[" C", " Q", " G"]) {
+                // "mac caffrey", "mac gregor"
+                index + 3
+            } else if self.contains(index + 1, &["C", "K", "Q"]) && !self.contains(index + 1, &["CE", "CI"]) {
+                index + 2
+            } else {
+                index + 1
+            }
+        }
+    }
+
+    /// Various Germanic words where C sounds as K, as in "bacher" or "macher".
+    fn c_as_k(&self, index: isize) -> bool {
+        if self.contains(index, &["CHIA"]) {
+            true
+        } else if index <= 1 || self.vowel(index - 2) || !self.contains(index - 1, &["ACH"]) {
+            false
+        } else {
+            let after = self.at(index + 2);
+            (after != Some('I') && after != Some('E')) || self.contains(index - 2, &["BACHER", "MACHER"])
+        }
+    }
+
+    fn ch(&mut self, index: isize) -> isize {
+        if index > 0 && self.contains(index, &["CHAE"]) {
+            // "michael"
+            self.add("K", "X");
+        } else if self.ch_greek(index) || self.ch_as_k(index) {
+            self.both("K");
+        } else if index > 0 {
+            if self.contains(0, &["MC"]) {
+                // "mchugh"
+                self.both("K");
+            } else {
+                self.add("X", "K");
+            }
+        } else {
+            self.both("X");
+        }
+
+        index + 2
+    }
+
+    /// Greek roots at the start of the word, such as "chemistry" or "chorus".
+    fn ch_greek(&self, index: isize) -> bool {
+        index == 0
+            && (self.contains(index + 1, &["HARAC", "HARIS"]) || self.contains(index + 1, &["HOR", "HYM", "HIA", "HEM"]))
+            && !self.contains(0, &["CHORE"])
+    }
+
+    /// Germanic, Greek or otherwise CH sounding as KH.
+    fn ch_as_k(&self, index: isize) -> bool {
+        self.germanic()
+            || self.contains(index - 2, &["ORCHES", "ARCHIT", "ORCHID"])
+            || self.contains(index + 2, &["T", "S"])
+            || ((self.contains(index - 1, &["A", "O", "U", "E"]) || index == 0)
+                && (self.contains(index + 2, &["L", "R", "N", "M", "B", "H", "F", "V", "W", " "]) || index + 1 == self.last()))
+    }
+
+    fn cc(&mut self, index: isize) -> isize {
+        if self.contains(index + 2, &["I", "E", "H"]) && !self.contains(index + 2, &["HU"]) {
+            if (index == 1 && self.is(index - 1, 'A')) || self.contains(index - 1, &["UCCEE", "UCCES"]) {
+                // "accident", "accede", "succeed"
+                self.both("KS");
+            } else {
+                // "bacci", "bertucci"
+                self.both("X");
+            }
+            index + 3
+        } else {
+            // Pierce's rule
+            self.both("K");
+            index + 2
+        }
+    }
+
+    fn d(&mut self, index: isize) -> isize {
+        if self.contains(index, &["DG"]) {
+            if self.contains(index + 2, &["I", "E", "Y"]) {
+                // "edge"
+                self.both("J");
+                index + 3
+            } else {
+                // "edgar"
+                self.both("TK");
+                index + 2
+            }
+        } else if self.contains(index, &["DT", "DD"]) {
+            self.both("T");
+            index + 2
+        } else {
+            self.both("T");
+            index + 1
+        }
+    }
+
+    fn g(&mut self, index: isize) -> isize {
+        if self.is(index + 1, 'H') {
+            return self.gh(index);
+        }
+
+        if self.is(index + 1, 'N') {
+            if index == 1 && self.vowel(0) && !self.slavo_germanic {
+                self.add("KN", "N");
+            } else if !self.contains(index + 2, &["EY"]) && !self.is(index + 1, 'Y') && !self.slavo_germanic {
+                self.add("N", "KN");
+            } else {
+                self.both("KN");
+            }
+            index + 2
+        } else if self.contains(index + 1, &["LI"]) && !self.slavo_germanic {
+            // "tagliaro"
+            self.add("KL", "L");
+            index + 2
+        } else if index == 0
+            && (self.is(index + 1, 'Y')
+                || self.contains(index + 1, &["ES", "EP", "EB", "EL", "EY", "IB", "IL", "IN", "IE", "EI", "ER"]))
+        {
+            // -ges-, -gep-, -gel-, -gie- at the beginning
+            self.add("K", "J");
+            index + 2
+        } else if (self.contains(index + 1, &["ER"]) || self.is(index + 1, 'Y'))
+            && !self.contains(0, &["DANGER", "RANGER", "MANGER"])
+            && !self.contains(index - 1, &["E", "I"])
+            && !self.contains(index - 1, &["RGY", "OGY"])
+        {
+            // -ger-, -gy-
+            self.add("K", "J");
+            index + 2
+        } else if self.contains(index + 1, &["E", "I", "Y"]) || self.contains(index - 1, &["AGGI", "OGGI"]) {
+            // Italian "biaggi"
+            if self.germanic() || self.contains(index + 1, &["ET"]) {
+                self.both("K");
+            } else if self.contains(index + 1, &["IER"]) {
+                self.both("J");
+            } else {
+                self.add("J", "K");
+            }
+            index + 2
+        } else if self.is(index + 1, 'G') {
+            self.both("K");
+            index + 2
+        } else {
+            self.both("K");
+            index + 1
+        }
+    }
+
+    fn gh(&mut self, index: isize) -> isize {
+        if index > 0 && !self.vowel(index - 1) {
+            self.both("K");
+        } else if index == 0 {
+            // "ghislane", "ghiradelli"
+            self.both(if self.is(index + 2, 'I') { "J" } else { "K" });
+        } else if (index > 1 && self.contains(index - 2, &["B", "H", "D"]))
+            || (index > 2 && self.contains(index - 3, &["B", "H", "D"]))
+            || (index > 3 && self.contains(index - 4, &["B", "H"]))
+        {
+            // Parker's rule, "hugh"
+        } else if index > 2 && self.is(index - 1, 'U') && self.contains(index - 3, &["C", "G", "L", "R", "T"]) {
+            // "laugh", "mclaughlin", "cough", "gough", "rough", "tough"
+            self.both("F");
+        } else if index > 0 && !self.is(index - 1, 'I') {
+            self.both("K");
+        }
+
+        index + 2
+    }
+
+    fn h(&mut self, index: isize) -> isize {
+        // Only kept when first before a vowel or between two vowels.
+        if (index == 0 || self.vowel(index - 1)) && self.vowel(index + 1) {
+            self.both("H");
+            index + 2
+        } else {
+            index + 1
+        }
+    }
+
+    fn j(&mut self, index: isize) -> isize {
+        if self.contains(index, &["JOSE"]) || self.contains(0, &["SAN "]) {
+            // Obviously Spanish, "jose", "san jacinto"
+            if (index == 0 && self.is(index + 4, ' ')) || self.word.len() == 4 || self.contains(0, &["SAN "]) {
+                self.both("H");
+            } else {
+                self.add("J", "H");
+            }
+            return index + 1;
+        }
+
+        if index == 0 {
+            // "yankelovich", "jankelowicz"
+            self.add("J", "A");
+        } else if self.vowel(index - 1)
+            && !self.slavo_germanic
+            && (self.is(index + 1, 'A') || self.is(index + 1, 'O'))
+        {
+            // Spanish pronunciation of "bajador"
+            self.add("J", "H");
+        } else if index == self.last() {
+            self.add("J", "");
+        } else if !self.contains(index + 1, &["L", "T", "K", "S", "N", "M", "B", "Z"])
+            && !self.contains(index - 1, &["S", "K", "L"])
+        {
+            self.both("J");
+        }
+
+        if self.is(index + 1, 'J') { index + 2 } else { index + 1 }
+    }
+
+    fn l(&mut self, index: isize) -> isize {
+        if !self.is(index + 1, 'L') {
+            self.both("L");
+            return index + 1;
+        }
+
+        // Spanish "cabrillo", "gallegos"
+        let last = self.last();
+        let spanish = (index == last - 2 && self.contains(index - 1, &["ILLO", "ILLA", "ALLE"]))
+            || ((self.contains(last - 1, &["AS", "OS"]) || self.contains(last, &["A", "O"]))
+                && self.contains(index - 1, &["ALLE"]));
+
+        if spanish {
+            self.add("L", "");
+        } else {
+            self.both("L");
+        }
+        index + 2
+    }
+
+    /// Whether the M takes the next letter too, as in "dumb" or "thumb".
+    fn m_doubled(&self, index: isize) -> bool {
+        self.is(index + 1, 'M')
+            || (self.contains(index - 1, &["UMB"]) && (index + 1 == self.last() || self.contains(index + 2, &["ER"])))
+    }
+
+    fn p(&mut self, index: isize) -> isize {
+        if self.is(index + 1, 'H') {
+            self.both("F");
+            index + 2
+        } else {
+            self.both("P");
+            if self.contains(index + 1, &["P", "B"]) { index + 2 } else { index + 1 }
+        }
+    }
+
+    fn r(&mut self, index: isize) -> isize {
+        // French "rogier", but not "hochmeier"
+        if index == self.last()
+            && !self.slavo_germanic
+            && self.contains(index - 2, &["IE"])
+            && !self.contains(index - 4, &["ME", "MA"])
+        {
+            self.add("", "R");
+        } else {
+            self.both("R");
+        }
+
+        if self.is(index + 1, 'R') { index + 2 } else { index + 1 }
+    }
+
+    fn s(&mut self, index: isize) -> isize {
+        if self.contains(index - 1, &["ISL", "YSL"]) {
+            // "island", "isle", "carlisle", "carlysle"
+            index + 1
+        } else if index == 0 && self.contains(index, &["SUGAR"]) {
+            self.add("X", "S");
+            index + 1
+        } else if self.contains(index, &["SH"]) {
+            // Germanic
+            if self.contains(index + 1, &["HEIM", "HOEK", "HOLM", "HOLZ"]) {
+                self.both("S");
+            } else {
+                self.both("X");
+            }
+            index + 2
+        } else if self.contains(index, &["SIO", "SIA"]) || self.contains(index, &["SIAN"]) {
+            // Italian and Armenian
+            if self.slavo_germanic {
+                self.both("S");
+            } else {
+                self.add("S", "X");
+            }
+            index + 3
+        } else if (index == 0 && self.contains(index + 1, &["M", "N", "L", "W"])) || self.contains(index + 1, &["Z"]) {
+            // German and anglicisations, "smith" matches "schmidt" and "snider" matches "schneider"
+            self.add("S", "X");
+            if self.contains(index + 1, &["Z"]) { index + 2 } else { index + 1 }
+        } else if self.contains(index, &["SC"]) {
+            self.sc(index)
+        } else {
+            // French "resnais", "artois"
+            if index == self.last() && self.contains(index - 2, &["AI", "OI"]) {
+                self.add("", "S");
+            } else {
+                self.both("S");
+            }
+            if self.contains(index + 1, &["S", "Z"]) { index + 2 } else { index + 1 }
+        }
+    }
+
+    fn sc(&mut self, index: isize) -> isize {
+        if self.is(index + 2, 'H') {
+            // Schlesinger's rule
+            if self.contains(index + 3, &["OO", "ER", "EN", "UY", "ED", "EM"]) {
+                // Dutch origin, "school", "schooner", "schermerhorn", "schenker"
+                if self.contains(index + 3, &["ER", "EN"]) {
+                    self.add("X", "SK");
+                } else {
+                    self.both("SK");
+                }
+            } else if index == 0 && !self.vowel(3) && !self.is(3, 'W') {
+                self.add("X", "S");
+            } else {
+                self.both("X");
+            }
+        } else if self.contains(index + 2, &["I", "E", "Y"]) {
+            self.both("S");
+        } else {
+            self.both("SK");
+        }
+
+        index + 3
+    }
+
+    fn t(&mut self, index: isize) -> isize {
+        if self.contains(index, &["TION"]) || self.contains(index, &["TIA", "TCH"]) {
+            self.both("X");
+            index + 3
+        } else if self.contains(index, &["TH"]) || self.contains(index, &["TTH"]) {
+            // "thomas", "thames" or Germanic
+            if self.contains(index + 2, &["OM", "AM"]) || self.germanic() {
+                self.both("T");
+            } else {
+                self.add("0", "T");
+            }
+            index + 2
+        } else {
+            self.both("T");
+            if self.contains(index + 1, &["T", "D"]) { index + 2 } else { index + 1 }
+        }
+    }
+
+    fn w(&mut self, index: isize) -> isize {
+        if self.contains(index, &["WR"]) {
+            self.both("R");
+            return index + 2;
+        }
+
+        if index == 0 && (self.vowel(index + 1) || self.contains(index, &["WH"])) {
+            if self.vowel(index + 1) {
+                // "wasserman" matches "vasserman"
+                self.add("A", "F");
+            } else {
+                // "uomo" matches "womo"
+                self.both("A");
+            }
+            index + 1
+        } else if (index == self.last() && self.vowel(index - 1))
+            || self.contains(index - 1, &["EWSKI", "EWSKY", "OWSKI", "OWSKY"])
+            || self.contains(0, &["SCH"])
+        {
+            // "arnow" matches "arnoff"
+            self.add("", "F");
+            index + 1
+        } else if self.contains(index, &["WICZ", "WITZ"]) {
+            // Polish, "filipowicz"
+            self.add("TS", "FX");
+            index + 4
+        } else {
+            index + 1
+        }
+    }
+
+    fn x(&mut self, index: isize) -> isize {
+        if index == 0 {
+            self.both("S");
+            return index + 1;
+        }
+
+        // French "breaux"
+        let silent = index == self.last()
+            && (self.contains(index - 3, &["IAU", "EAU"]) || self.contains(index - 2, &["AU", "OU"]));
+        if !silent {
+            self.both("KS");
+        }
+
+        if self.contains(index + 1, &["C", "X"]) { index + 2 } else { index + 1 }
+    }
+
+    fn z(&mut self, index: isize) -> isize {
+        if self.is(index + 1, 'H') {
+            // Chinese pinyin, "zhao"
+            self.both("J");
+            return index + 2;
+        }
+
+        if self.contains(index + 1, &["ZO", "ZI", "ZA"]) || (self.slavo_germanic && index > 0 && !self.is(index - 1, 'T')) {
+            self.add("S", "TS");
+        } else {
+            self.both("S");
+        }
+
+        if self.is(index + 1, 'Z') { index + 2 } else { index + 1 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double_metaphone() {
+        let encoder = DoubleMetaphone::default();
+
+        for (word, primary, alternate) in [
+            ("smith", "SM0", "XMT"),
+            ("schmidt", "XMT", "SMT"),
+            ("xavier", "SF", "SFR"),
+            ("jose", "HS", "HS"),
+            ("cabrillo", "KPRL", "KPR"),
+            ("gallegos", "KLKS", "KKS"),
+            ("dumb", "TM", "TM"),
+            ("arnow", "ARN", "ARNF"),
+            ("thumbail", "0MPL", "TMPL"),
+            ("jankelowicz", "JNKL", "ANKL"),
+            ("czerny", "SRN", "XRN"),
+            ("michael", "MKL", "MXL"),
+            ("edge", "AJ", "AJ"),
+            ("gough", "KF", "KF"),
+            ("knight", "NT", "NT"),
+            ("caesar", "SSR", "SSR"),
+            ("campbell", "KMPL", "KMPL"),
+            ("bacchus", "PKS", "PKS"),
+            ("chianti", "KNT", "KNT"),
+            ("mchugh", "MK", "MK"),
+            ("focaccia", "FKX", "FKX"),
+            ("kubectl", "KPKT", "KPKT"),
+            ("kubctl", "KPKT", "KPKT"),
+        ] {
+            let codes = encoder.encode(word);
+            assert_eq!(codes[0], primary, "{}", word);
+            assert_eq!(codes.last().unwrap(), alternate, "{}", word);
+            assert_eq!(codes.len(), if primary == alternate { 1 } else { 2 }, "{}", word);
+        }
+
+        assert!(encoder.encode("").is_empty());
+    }
+}
@@ -0,0 +1,54 @@
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Removes diacritics and spells out ligatures, so `Ärger` becomes `Arger` and `Straße` becomes `Strasse`.
+///
+/// Letters without a decomposition to ASCII are kept as they are.
+pub(crate) fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+
+    for c in text.nfd().filter(|c| !is_combining_mark(*c)) {
+        match c {
+            'ß' => folded.push_str("ss"),
+            'ẞ' => folded.push_str("SS"),
+            'æ' => folded.push_str("ae"),
+            'Æ' => folded.push_str("AE"),
+            'œ' => folded.push_str("oe"),
+            'Œ' => folded.push_str("OE"),
+            'þ' => folded.push_str("th"),
+            'Þ' => folded.push_str("TH"),
+            'ø' => folded.push('o'),
+            'Ø' => folded.push('O'),
+            'ł' => folded.push('l'),
+            'Ł' => folded.push('L'),
+            'đ' | 'ð' => folded.push('d'),
+            'Đ' | 'Ð' => folded.push('D'),
+            'ı' => folded.push('i'),
+            c => folded.push(c),
+        }
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold() {
+        for (text, folded) in [
+            ("Ärger", "Arger"),
+            ("Straße", "Strasse"),
+            ("canción", "cancion"),
+            ("niño", "nino"),
+            ("Łódź", "Lodz"),
+            ("Œuvre", "OEuvre"),
+            ("e\u{301}", "e"), // Already decomposed
+            ("docker", "docker"),
+            ("日本", "日本"),
+        ] {
+            assert_eq!(fold(text), folded);
+        }
+    }
+}
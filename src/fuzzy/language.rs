@@ -0,0 +1,77 @@
+use crate::fuzzy::phonetic::words;
+
+/// Languages with their own phonetic encoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Language {
+    German,
+    Spanish,
+}
+
+/// Frequent words that give the language away, short texts like descriptions rarely miss all of them.
+const GERMAN_WORDS: &[&str] = &[
+    "der", "die", "das", "den", "dem", "und", "ist", "nicht", "mit", "für", "ein", "eine", "einen", "auf",
+    "aus", "alle", "oder", "zum", "zur", "von", "im", "ins", "wird", "werden", "anzeigen", "löschen", "datei",
+    "dateien", "verzeichnis",
+];
+const SPANISH_WORDS: &[&str] = &[
+    "el", "la", "los", "las", "del", "que", "en", "un", "una", "para", "con", "por", "es", "se", "todos",
+    "todas", "lista", "listar", "mostrar", "borrar", "eliminar", "archivo", "archivos", "directorio", "rama",
+];
+const ENGLISH_WORDS: &[&str] = &[
+    "the", "of", "and", "to", "is", "for", "with", "from", "all", "list", "show", "delete", "remove", "file",
+    "files", "directory", "branch", "an", "on", "into",
+];
+
+/// Letters that only one of the languages uses, they weigh more than a word.
+const GERMAN_LETTERS: &[char] = &['ä', 'ö', 'ü', 'ß'];
+const SPANISH_LETTERS: &[char] = &['ñ', 'á', 'é', 'í', 'ó', 'ú', '¿', '¡'];
+const LETTER_WEIGHT: usize = 2;
+
+/// Guesses the language of a short text, `None` when it looks English or there isn't enough to tell.
+pub(crate) fn detect(text: &str) -> Option<Language> {
+    let text = text.to_lowercase();
+
+    let score = |words_of: &[&str], letters: &[char]| {
+        let words = words(&text).filter(|word| words_of.contains(word)).count();
+        let letters = text.chars().filter(|c| letters.contains(c)).count();
+        words + letters * LETTER_WEIGHT
+    };
+
+    // "y" is too short for `words`, it is counted on its own.
+    let conjunctions = text.split_whitespace().filter(|word| *word == "y").count();
+
+    let german = score(GERMAN_WORDS, GERMAN_LETTERS);
+    let spanish = score(SPANISH_WORDS, SPANISH_LETTERS) + conjunctions;
+    let english = score(ENGLISH_WORDS, &[]);
+
+    if german > spanish && german > english {
+        Some(Language::German)
+    } else if spanish > german && spanish > english {
+        Some(Language::Spanish)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        for (text, language) in [
+            ("Alle Container anzeigen", Some(Language::German)),
+            ("Löscht die Datei", Some(Language::German)),
+            ("Größe des Verzeichnisses", Some(Language::German)),
+            ("Listar todos los contenedores", Some(Language::Spanish)),
+            ("Cambiar de rama y borrar la anterior", Some(Language::Spanish)),
+            ("Configuración", Some(Language::Spanish)),
+            ("Show all the containers", None),
+            ("Delete the branch", None),
+            ("docker", None),
+            ("", None),
+        ] {
+            assert_eq!(detect(text), language, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::fuzzy::{is_vowel, letters, PhoneticEncoder};
+
+/// Lawrence Philips' original Metaphone, consonant sounds of English words.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Metaphone {
+    pub(crate) max_length: usize,
+}
+
+impl Default for Metaphone {
+    fn default() -> Self {
+        Self { max_length: 4 }
+    }
+}
+
+/// Vowels that soften the consonant before them.
+fn is_front_vowel(c: Option<char>) -> bool {
+    matches!(c, Some('E' | 'I' | 'Y'))
+}
+
+impl PhoneticEncoder for Metaphone {
+    fn encode(&self, word: &str) -> Vec<String> {
+        let mut word = letters(word);
+
+        match word.as_slice() {
+            [] => return vec![],
+            // Silent first letters.
+            ['A', 'E', ..] | ['G', 'N', ..] | ['K', 'N', ..] | ['P', 'N', ..] | ['W', 'R', ..] => {
+                word.remove(0);
+            },
+            ['W', 'H', ..] => {
+                word.remove(1);
+            },
+            ['X', ..] => word[0] = 'S',
+            _ => {},
+        }
+
+        let at = |index: usize| word.get(index).copied();
+        let starts = |index: usize, pattern: &str| {
+            pattern.chars().enumerate().all(|(offset, c)| at(index + offset) == Some(c))
+        };
+
+        let mut code = String::with_capacity(self.max_length + 1);
+        let last = word.len() - 1;
+
+        for (n, &letter) in word.iter().enumerate() {
+            if code.len() >= self.max_length {
+                break;
+            }
+
+            let previous = n.checked_sub(1).and_then(at);
+            let next = at(n + 1);
+
+            // Doubled letters sound as one, except C.
+            if letter != 'C' && previous == Some(letter) {
+                continue;
+            }
+
+            match letter {
+                'A' | 'E' | 'I' | 'O' | 'U' if n == 0 => code.push(letter),
+                'A' | 'E' | 'I' | 'O' | 'U' => {},
+                // Silent in -MB at the end, as in "dumb".
+                'B' if previous == Some('M') && n == last => {},
+                'C' => {
+                    if previous == Some('S') && is_front_vowel(next) {
+                        // Silent in -SCI-, -SCE-, -SCY-.
+                    } else if starts(n, "CIA") {
+                        code.push('X');
+                    } else if is_front_vowel(next) {
+                        code.push('S');
+                    } else if previous == Some('S') && next == Some('H') {
+                        code.push('K');
+                    } else if next == Some('H') {
+                        let hard = n == 0 && at(2).is_some_and(is_vowel);
+                        code.push(if hard { 'K' } else { 'X' });
+                    } else {
+                        code.push('K');
+                    }
+                },
+                'D' if next == Some('G') && is_front_vowel(at(n + 2)) => code.push('J'),
+                'D' => code.push('T'),
+                'G' => {
+                    let silent = (next == Some('H') && (n + 1 == last || !at(n + 2).is_some_and(is_vowel)))
+                        || (n > 0 && (word[n..] == ['G', 'N'] || word[n..] == ['G', 'N', 'E', 'D']))
+                        // Already written as J by the D in -DGE-, -DGI-, -DGY-.
+                        || (previous == Some('D') && is_front_vowel(next));
+
+                    if !silent {
+                        code.push(if is_front_vowel(next) { 'J' } else { 'K' });
+                    }
+                },
+                'H' => {
+                    let after_modifier = matches!(previous, Some('C' | 'S' | 'P' | 'T' | 'G'));
+                    if n != last && !after_modifier && next.is_some_and(is_vowel) {
+                        code.push('H');
+                    }
+                },
+                'K' if previous == Some('C') => {},
+                'P' if next == Some('H') => code.push('F'),
+                'Q' => code.push('K'),
+                'S' if starts(n, "SH") || starts(n, "SIO") || starts(n, "SIA") => code.push('X'),
+                'T' if starts(n, "TIA") || starts(n, "TIO") => code.push('X'),
+                'T' if starts(n, "TCH") => {},
+                'T' if next == Some('H') => code.push('0'),
+                'V' => code.push('F'),
+                'W' | 'Y' => {
+                    if next.is_some_and(is_vowel) {
+                        code.push(letter);
+                    }
+                },
+                'X' => code.push_str("KS"),
+                'Z' => code.push('S'),
+                _ => code.push(letter),
+            }
+        }
+
+        code.truncate(self.max_length);
+        vec![code]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metaphone() {
+        let metaphone = Metaphone::default();
+
+        for (word, code) in [
+            ("howl", "HL"),
+            ("testing", "TSTN"),
+            ("the", "0"),
+            ("quick", "KK"),
+            ("brown", "BRN"),
+            ("fox", "FKS"),
+            ("jumped", "JMPT"),
+            ("over", "OFR"),
+            ("lazy", "LS"),
+            ("dogs", "TKS"),
+            ("knight", "NT"), // Silent K and GH
+            ("wright", "RT"),
+            ("white", "WT"),
+            ("xylophone", "SLFN"),
+            ("dumb", "TM"),
+            ("science", "SNS"),
+            ("edge", "EJ"),
+            ("kubectl", "KBKT"),
+            ("kubctl", "KBKT"),
+        ] {
+            assert_eq!(metaphone.encode(word), vec![code], "{}", word);
+        }
+
+        assert!(metaphone.encode("").is_empty());
+    }
+}
@@ -1,5 +1,83 @@
+use serde::Deserialize;
+
+mod cologne;
+mod double_metaphone;
+mod fold;
+mod language;
+mod metaphone;
+mod nysiis;
+mod phonetic;
 mod soundex;
+mod spanish;
 
 pub(crate) mod prelude {
-    
-}
\ No newline at end of file
+    pub(crate) use super::language::detect;
+    pub(crate) use super::phonetic::{encode_words, PhoneticQuery};
+    pub(crate) use super::PhoneticEncoder;
+}
+
+/// Algorithm that turns a word into codes, words that sound alike share a code.
+pub(crate) trait PhoneticEncoder: Send + Sync {
+    /// Codes for the word, most algorithms give one code but some give alternative
+    /// pronunciations too. Words without anything to encode give no codes.
+    fn encode(&self, word: &str) -> Vec<String>;
+}
+
+/// Phonetic algorithm selected in the configuration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Phonetic {
+    Soundex,
+    Metaphone,
+    #[default]
+    DoubleMetaphone,
+    Nysiis,
+    /// Kölner Phonetik, for German.
+    Cologne,
+    SpanishSoundex,
+}
+
+impl Phonetic {
+    pub(crate) const ALL: [Phonetic; 6] = [
+        Phonetic::Soundex,
+        Phonetic::Metaphone,
+        Phonetic::DoubleMetaphone,
+        Phonetic::Nysiis,
+        Phonetic::Cologne,
+        Phonetic::SpanishSoundex,
+    ];
+
+    pub(crate) fn encoder(self) -> Box<dyn PhoneticEncoder> {
+        match self {
+            Phonetic::Soundex => Box::new(soundex::Soundex),
+            Phonetic::Metaphone => Box::new(metaphone::Metaphone::default()),
+            Phonetic::DoubleMetaphone => Box::new(double_metaphone::DoubleMetaphone::default()),
+            Phonetic::Nysiis => Box::new(nysiis::Nysiis::default()),
+            Phonetic::Cologne => Box::new(cologne::Cologne),
+            Phonetic::SpanishSoundex => Box::new(spanish::SpanishSoundex),
+        }
+    }
+
+    /// Algorithm made for texts in the language.
+    pub(crate) fn for_language(language: language::Language) -> Self {
+        match language {
+            language::Language::German => Phonetic::Cologne,
+            language::Language::Spanish => Phonetic::SpanishSoundex,
+        }
+    }
+}
+
+/// Uppercase ASCII letters of a word, the only ones the encoders know how to handle.
+///
+/// Diacritics are folded first, so `Müller` gives the same letters as `Muller`.
+fn letters(word: &str) -> Vec<char> {
+    fold::fold(word)
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'A' | 'E' | 'I' | 'O' | 'U')
+}
@@ -0,0 +1,140 @@
+use crate::fuzzy::{is_vowel, letters, PhoneticEncoder};
+
+/// New York State Identification and Intelligence System code, keeps vowel positions
+/// as `A` so it tells apart words that Soundex mixes up.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Nysiis {
+    /// Codes are cut to this length, the original algorithm uses six letters.
+    pub(crate) max_length: Option<usize>,
+}
+
+impl Default for Nysiis {
+    fn default() -> Self {
+        Self { max_length: Some(6) }
+    }
+}
+
+fn replace_prefix(word: &mut Vec<char>, from: &str, to: &str) -> bool {
+    let from = from.chars().collect::<Vec<_>>();
+    if word.starts_with(&from) {
+        word.splice(..from.len(), to.chars());
+        true
+    } else {
+        false
+    }
+}
+
+fn replace_suffix(word: &mut Vec<char>, from: &str, to: &str) -> bool {
+    let from = from.chars().collect::<Vec<_>>();
+    if word.ends_with(&from) {
+        let start = word.len() - from.len();
+        word.splice(start.., to.chars());
+        true
+    } else {
+        false
+    }
+}
+
+impl PhoneticEncoder for Nysiis {
+    fn encode(&self, word: &str) -> Vec<String> {
+        let mut word = letters(word);
+        if word.is_empty() {
+            return vec![];
+        }
+
+        let _ = replace_prefix(&mut word, "MAC", "MCC")
+            || replace_prefix(&mut word, "KN", "NN")
+            || replace_prefix(&mut word, "K", "C")
+            || replace_prefix(&mut word, "PH", "FF")
+            || replace_prefix(&mut word, "PF", "FF")
+            || replace_prefix(&mut word, "SCH", "SSS");
+
+        let _ = replace_suffix(&mut word, "EE", "Y")
+            || replace_suffix(&mut word, "IE", "Y")
+            || ["DT", "RT", "RD", "NT", "ND"].iter().any(|suffix| replace_suffix(&mut word, suffix, "D"));
+
+        let mut key = vec![word[0]];
+
+        let mut index = 1;
+        while index < word.len() {
+            let previous = word[index - 1];
+            let current = word[index];
+            let next = word.get(index + 1).copied();
+            let after = word.get(index + 2).copied();
+
+            // Transcode the current letter, some rules rewrite the following ones too.
+            let transcoded: &[char] = match current {
+                'E' if next == Some('V') => &['A', 'F'],
+                c if is_vowel(c) => &['A'],
+                'Q' => &['G'],
+                'Z' => &['S'],
+                'M' => &['N'],
+                'K' if next == Some('N') => &['N', 'N'],
+                'K' => &['C'],
+                'S' if next == Some('C') && after == Some('H') => &['S', 'S', 'S'],
+                'P' if next == Some('H') => &['F', 'F'],
+                'H' if !is_vowel(previous) || !next.is_some_and(is_vowel) => &[previous],
+                'W' if is_vowel(previous) => &[previous],
+                _ => &[current],
+            };
+
+            let end = (index + transcoded.len()).min(word.len());
+            word.splice(index..end, transcoded.iter().copied().take(end - index));
+
+            if word[index] != word[index - 1] {
+                key.push(word[index]);
+            }
+
+            index += 1;
+        }
+
+        if key.len() > 1 {
+            if key.last() == Some(&'S') {
+                key.pop();
+            }
+            if key.ends_with(&['A', 'Y']) {
+                key.remove(key.len() - 2);
+            }
+            if key.last() == Some(&'A') {
+                key.pop();
+            }
+        }
+
+        if let Some(max_length) = self.max_length {
+            key.truncate(max_length);
+        }
+
+        vec![key.into_iter().collect()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nysiis() {
+        let nysiis = Nysiis::default();
+
+        for (word, code) in [
+            ("brown", "BRAN"),
+            ("smith", "SNAT"),
+            ("johnson", "JANSAN"),
+            ("williams", "WALAN"),
+            ("jones", "JAN"),
+            ("miller", "MALAR"),
+            ("davis", "DAV"),
+            ("knight", "NAGT"),
+            ("mitchell", "MATCAL"),
+            ("macintosh", "MCANT"),
+            ("bishop", "BASAP"),
+            ("carlson", "CARLSA"),
+            ("greene", "GRAN"),
+            ("kubectl", "CABACT"),
+        ] {
+            assert_eq!(nysiis.encode(word), vec![code], "{}", word);
+        }
+
+        assert!(nysiis.encode("").is_empty());
+    }
+}
@@ -0,0 +1,77 @@
+use crate::fuzzy::PhoneticEncoder;
+
+/// Words shorter than this are too ambiguous to be compared by sound.
+const MIN_WORD_LENGTH: usize = 2;
+
+/// Words of a text that can be encoded, runs of letters long enough to mean something.
+pub(crate) fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphabetic())
+        .filter(|word| word.chars().count() >= MIN_WORD_LENGTH)
+}
+
+/// Query encoded word by word, to find texts that sound like it even if they are spelled differently.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PhoneticQuery {
+    /// Codes of each word of the query.
+    words: Vec<Vec<String>>,
+}
+
+impl PhoneticQuery {
+    /// Encodes the words of the query, `None` if there is nothing to compare.
+    pub(crate) fn new(query: &str, encoder: &dyn PhoneticEncoder) -> Option<Self> {
+        let words = words(query)
+            .map(|word| encoder.encode(word))
+            .filter(|codes| !codes.is_empty())
+            .collect::<Vec<_>>();
+
+        (!words.is_empty()).then_some(Self { words })
+    }
+
+    /// Whether every word of the query sounds like some word encoded in `codes`, see [`encode_words`].
+    pub(crate) fn matches(&self, codes: &[String]) -> bool {
+        self.words
+            .iter()
+            .all(|word| word.iter().any(|code| codes.contains(code)))
+    }
+}
+
+/// Codes of the words of the texts, sorted and without repetitions.
+pub(crate) fn encode_words<'t>(texts: impl IntoIterator<Item = &'t str>, encoder: &dyn PhoneticEncoder) -> Vec<String> {
+    let mut codes = texts
+        .into_iter()
+        .flat_map(words)
+        .flat_map(|word| encoder.encode(word))
+        .collect::<Vec<_>>();
+    codes.sort_unstable();
+    codes.dedup();
+    codes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::Phonetic;
+
+    #[test]
+    fn test_phonetic_query() {
+        for phonetic in [Phonetic::Soundex, Phonetic::Metaphone, Phonetic::DoubleMetaphone, Phonetic::Nysiis] {
+            let encoder = phonetic.encoder();
+            let encoder = encoder.as_ref();
+
+            let matches = |query: &PhoneticQuery, texts: &[&str]| query.matches(&encode_words(texts.iter().copied(), encoder));
+
+            let query = PhoneticQuery::new("grepp", encoder).unwrap();
+            assert!(matches(&query, &["grep -rn <pattern> .", "Search text"]), "{:?}", phonetic);
+            assert!(!matches(&query, &["git rebase", "Rebase the branch"]), "{:?}", phonetic);
+
+            let query = PhoneticQuery::new("kubctl logs", encoder).unwrap();
+            assert!(!matches(&query, &["kubectl get pods"]), "{:?}", phonetic);
+            // NYSIIS keeps the position of the vowels, so it doesn't forgive a missing one.
+            if phonetic != Phonetic::Nysiis {
+                assert!(matches(&query, &["kubectl logs <pod>"]), "{:?}", phonetic);
+            }
+
+            assert_eq!(PhoneticQuery::new("- 1 .", encoder), None);
+        }
+    }
+}
@@ -1,93 +1,90 @@
-pub(crate) fn phoneme_encode(word: &str) -> String {
-    let acc = word
-        .chars()
-        .fold(String::with_capacity(word.len()), |mut acc, letter| 
-            if acc.is_empty() {
-                acc.push(letter.to_ascii_uppercase());
-                acc
-            } else {
-                let length = acc.len();
-                let previous = if length > 1 {
-                    acc.chars().nth(length - 1).unwrap()
-                } else {
-                    ' ' // Dummy value
-                };
+use crate::fuzzy::{letters, PhoneticEncoder};
 
-                match (previous, letter) {
-                    ('1', 'b' | 'f' | 'p' | 'v') => return acc,
-                    ('2', 'c' | 'g' | 'j' | 'k' | 'q' | 's' | 'x' | 'z') => return acc,
-                    ('3', 'd' | 't') => return acc,
-                    ('4', 'l') => return acc,
-                    ('5', 'm' | 'n') => return acc,
-                    ('6', 'r') => return acc,
-                    (_, 'b' | 'f' | 'p' | 'v') => acc.push(char::from_digit(1, 10).unwrap()),
-                    (_, 'c' | 'g' | 'j' | 'k' | 'q' | 's' | 'x' | 'z') => acc.push(char::from_digit(2, 10).unwrap()),
-                    (_, 'd' | 't') => acc.push(char::from_digit(3, 10).unwrap()),
-                    (_, 'l') => acc.push(char::from_digit(4, 10).unwrap()),
-                    (_, 'm' | 'n') => acc.push(char::from_digit(5, 10).unwrap()),
-                    (_, 'r') => acc.push(char::from_digit(6, 10).unwrap()),
-                    _ => acc.push(letter.to_ascii_uppercase()),
-                }
+/// American Soundex, the first letter followed by three digits for the consonants that follow.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Soundex;
 
-                acc
-            });
+impl PhoneticEncoder for Soundex {
+    fn encode(&self, word: &str) -> Vec<String> {
+        let letters = letters(word);
+        let Some(&first) = letters.first() else {
+            return vec![];
+        };
 
-    let mut numeric = acc
-        .chars()
-        .skip(1)
-        .fold(String::with_capacity(acc.len()), |mut acc, letter| {
-            let previous = if acc.len() > 1 {
-                acc.chars().nth(acc.len() - 1).unwrap()
-            } else {
-                ' ' // Dummy value
-            };
+        let mut code = String::with_capacity(4);
+        code.push(first);
 
-            let previous_previous = if acc.len() > 2 {
-                acc.chars().nth(acc.len() - 2).unwrap()
-            } else {
-                ' ' // Dummy value
-            };
+        // The first letter is kept as is, but its digit still counts for adjacency.
+        let mut previous = digit(first);
+        for &letter in &letters[1..] {
+            match letter {
+                // H and W don't separate consonants with the same digit.
+                'H' | 'W' => continue,
+                // Vowels do, the same digit is written again after them.
+                'A' | 'E' | 'I' | 'O' | 'U' | 'Y' => previous = None,
+                _ => {
+                    let current = digit(letter);
+                    if current != previous {
+                        code.extend(current);
+                    }
+                    previous = current;
+                },
+            }
 
-            match (previous_previous, previous, letter) {
-                (_, 'A' | 'E' | 'I' | 'O' | 'U' | 'Y', _) => acc.push(letter),
-                (prev, 'H' | 'W', current) if prev == current => return acc,
-                (prev, 'H' | 'W', current) if prev != current => acc.push(letter),
-                _ => acc.push(letter),
+            if code.len() == 4 {
+                break;
             }
-            
-            acc
-        })
-        .chars()
-        .filter(|c| c.is_ascii_digit())
-        .collect::<String>();
+        }
 
-    numeric = match numeric.len() {
-        0 => "000".to_string(),
-        1 => format!("{}00", numeric),
-        2 => format!("{}0", numeric),
-        _ => numeric[..3].to_string(),
-    };
+        while code.len() < 4 {
+            code.push('0');
+        }
 
-    format!("{}{}",
-        acc.chars().nth(0).unwrap(), // First character
-        numeric // Numeric characters
-    )
+        vec![code]
+    }
+}
+
+fn digit(letter: char) -> Option<char> {
+    match letter {
+        'B' | 'F' | 'P' | 'V' => Some('1'),
+        'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => Some('2'),
+        'D' | 'T' => Some('3'),
+        'L' => Some('4'),
+        'M' | 'N' => Some('5'),
+        'R' => Some('6'),
+        _ => None,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn phoneme_encode(word: &str) -> String {
+        Soundex.encode(word).remove(0)
+    }
+
     #[test]
     fn test_phoneme_encode() {
         assert_eq!(phoneme_encode("stewart"), phoneme_encode("stuart")); // Similar sounding
-        assert_eq!(phoneme_encode("allricht"), "A462");
-        assert_eq!(phoneme_encode("hanselmann"), "H524");
-        assert_eq!(phoneme_encode("roses"), "R220"); // Vowel letter separator
-        assert_eq!(phoneme_encode("carwruth"), "C630"); // H or W letter separator
-        assert_eq!(phoneme_encode("gutierrez"), "G362"); // Double consonant
-        assert_eq!(phoneme_encode("campbell"), "C514"); // Same type of consonant
-        assert_eq!(phoneme_encode("zita"), "Z300"); // Single consonant
         assert_eq!(phoneme_encode("schafer"), phoneme_encode("shaeffer")); // Similar sounding
+
+        for (word, code) in [
+            ("allricht", "A462"),
+            ("hanselmann", "H524"),
+            ("roses", "R220"), // Vowel letter separator
+            ("carwruth", "C630"), // H or W letter separator
+            ("gutierrez", "G362"), // Double consonant
+            ("campbell", "C514"), // Same type of consonant
+            ("zita", "Z300"), // Single consonant
+            ("schafer", "S160"), // First letter with the same digit as the next one
+            ("grepp", "G610"),
+            ("kubectl", "K123"),
+        ] {
+            assert_eq!(phoneme_encode(word), code, "{}", word);
+        }
+
+        assert!(Soundex.encode("").is_empty());
+        assert!(Soundex.encode("42").is_empty());
     }
-}
\ No newline at end of file
+}
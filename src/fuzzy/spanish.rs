@@ -0,0 +1,135 @@
+use crate::fuzzy::{is_vowel, letters, PhoneticEncoder};
+
+/// Soundex adapted to Spanish spelling, letters that sound the same are rewritten first so
+/// `vaca` and `baca`, `cena` and `sena` or `llave` and `yave` share a code.
+///
+/// The first sound is kept as a letter, the following ones become digits:
+/// `B P` 1, `F` 2, `D T` 3, `S` 4 (with soft `C`, `Z`, `X` and `CH`), `L Y` 5 (with `LL`),
+/// `M N` 6, `K G` 7 (with hard `C` and `Q`), `J` 8 (with soft `G`) and `R` 9.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct SpanishSoundex;
+
+/// Length of the codes, padded with zeros.
+const LENGTH: usize = 4;
+
+fn is_soft(c: Option<char>) -> bool {
+    matches!(c, Some('E' | 'I'))
+}
+
+/// Rewrites the word with a single letter for each sound, `H` is silent and dropped.
+fn sounds(word: &[char]) -> Vec<char> {
+    let mut sounds = Vec::with_capacity(word.len());
+
+    let mut n = 0;
+    while n < word.len() {
+        let next = word.get(n + 1).copied();
+        let after = word.get(n + 2).copied();
+
+        let (sound, length) = match word[n] {
+            'C' if next == Some('H') => (Some('X'), 2),
+            'C' if is_soft(next) => (Some('S'), 1),
+            'C' | 'K' => (Some('K'), 1),
+            'Q' if next == Some('U') && is_soft(after) => (Some('K'), 2),
+            'Q' => (Some('K'), 1),
+            'G' if next == Some('U') && is_soft(after) => (Some('G'), 2),
+            'G' if is_soft(next) => (Some('J'), 1),
+            'L' if next == Some('L') => (Some('Y'), 2),
+            // Y is a vowel when it doesn't come before one, as in "rey".
+            'Y' if !next.is_some_and(is_vowel) => (Some('I'), 1),
+            'V' | 'W' => (Some('B'), 1),
+            'Z' => (Some('S'), 1),
+            'H' => (None, 1),
+            c => (Some(c), 1),
+        };
+
+        sounds.extend(sound);
+        n += length;
+    }
+
+    sounds
+}
+
+fn digit(sound: char) -> Option<char> {
+    match sound {
+        'B' | 'P' => Some('1'),
+        'F' => Some('2'),
+        'D' | 'T' => Some('3'),
+        'S' | 'X' => Some('4'),
+        'L' | 'Y' => Some('5'),
+        'M' | 'N' => Some('6'),
+        'K' | 'G' => Some('7'),
+        'J' => Some('8'),
+        'R' => Some('9'),
+        _ => None,
+    }
+}
+
+impl PhoneticEncoder for SpanishSoundex {
+    fn encode(&self, word: &str) -> Vec<String> {
+        let sounds = sounds(&letters(word));
+        let Some((&first, rest)) = sounds.split_first() else {
+            return vec![];
+        };
+
+        let mut code = String::with_capacity(LENGTH);
+        code.push(match first {
+            // The first letter stands for its whole group.
+            'X' => 'S',
+            c => c,
+        });
+
+        let mut previous = digit(first);
+        for &sound in rest {
+            if code.len() == LENGTH {
+                break;
+            }
+
+            let current = digit(sound);
+            if current.is_some() && current != previous {
+                code.extend(current);
+            }
+            previous = current;
+        }
+
+        while code.len() < LENGTH {
+            code.push('0');
+        }
+
+        vec![code]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spanish_soundex() {
+        for (word, code) in [
+            ("guitarra", "G390"),
+            ("perro", "P900"),
+            ("chorizo", "S940"),
+            ("Muñoz", "M640"),
+            ("quesadilla", "K435"),
+            ("año", "A600"),
+        ] {
+            assert_eq!(SpanishSoundex.encode(word), vec![code], "{}", word);
+        }
+
+        for (word, alike) in [
+            ("vaca", "baca"),
+            ("hola", "ola"),
+            ("cena", "sena"),
+            ("llave", "yave"),
+            ("gente", "jente"),
+            ("queso", "keso"),
+            ("zapato", "sapato"),
+            ("canción", "cancion"),
+        ] {
+            assert_eq!(SpanishSoundex.encode(word), SpanishSoundex.encode(alike), "{}", word);
+        }
+
+        assert!(SpanishSoundex.encode("").is_empty());
+        assert!(SpanishSoundex.encode("h").is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+//! Generators of the variables, shell commands whose output lines are suggested as values.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::Duration;
+
+use tokio::process::Command;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::AbortHandle;
+
+use crate::ansi;
+use crate::event::Event;
+use crate::execution::ProcessGroup;
+
+/// Output of a generator, sent as an [`Event::Generated`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generated {
+    /// Identifier given by [`Generators::run`], to tell apart outdated runs.
+    pub id: u64,
+    pub variable: String,
+    /// Non empty output lines, or why the generator failed.
+    pub result: Result<Vec<String>, String>,
+}
+
+/// Runs generators in the background of the tokio runtime, one at a time for each variable.
+#[derive(Debug)]
+pub struct Generators {
+    sender: UnboundedSender<Event>,
+    timeout: Duration,
+    running: HashMap<String, AbortHandle>,
+    next_id: u64,
+}
+
+impl Generators {
+    pub fn new(sender: UnboundedSender<Event>, timeout: Duration) -> Self {
+        Self {
+            sender,
+            timeout,
+            running: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Starts the generator of the variable, cancelling the one still running for it.
+    ///
+    /// The values of the other variables are given to the command as environment variables.
+    pub fn run(&mut self, variable: &str, command: &str, env: HashMap<String, String>) -> u64 {
+        self.next_id += 1;
+        let id = self.next_id;
+
+        let sender = self.sender.clone();
+        let timeout = self.timeout;
+        let variable = variable.to_string();
+        let command = command.to_string();
+        let name = variable.clone();
+
+        let task = tokio::spawn(async move {
+            let result = generate(&command, env, timeout).await;
+            let _ = sender.send(Event::Generated(Generated { id, variable, result }));
+        });
+
+        if let Some(previous) = self.running.insert(name, task.abort_handle()) {
+            previous.abort();
+        }
+
+        id
+    }
+
+    /// Stops every generator, their results are never sent.
+    pub fn cancel(&mut self) {
+        for (_, task) in self.running.drain() {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for Generators {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Runs the command with `sh`, it is killed with everything it started if it takes longer than `timeout` or the
+/// task is aborted.
+pub async fn generate(command: &str, env: HashMap<String, String>, timeout: Duration) -> Result<Vec<String>, String> {
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .process_group(0)
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| err.to_string())?;
+
+    let group = ProcessGroup::of(&child);
+    let output = tokio::time::timeout(timeout, child.wait_with_output())
+        .await
+        .map_err(|_| format!("timed out after {}s", timeout.as_secs_f32()))?
+        .map_err(|err| err.to_string())?;
+    group.exited();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match stderr.lines().find(|line| !line.trim().is_empty()) {
+            Some(line) => line.trim().to_string(),
+            None => output.status.to_string(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(ansi::strip)
+        .filter(|line| !line.trim().is_empty())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_generate() {
+        let timeout = Duration::from_secs(5);
+        let env = HashMap::from([("branch".to_string(), "main".to_string())]);
+
+        assert_eq!(generate("printf 'a\\n\\nb\\n'", HashMap::new(), timeout).await, Ok(vec!["a".into(), "b".into()]));
+        assert_eq!(generate("echo $branch", env, timeout).await, Ok(vec!["main".into()]));
+        assert_eq!(generate("printf '\\033[32m* main\\033[m\\n'", HashMap::new(), timeout).await, Ok(vec!["* main".into()]));
+        assert_eq!(generate("echo oops >&2; exit 3", HashMap::new(), timeout).await, Err("oops".into()));
+        assert_eq!(generate("exit 3", HashMap::new(), timeout).await, Err("exit status: 3".into()));
+        assert_eq!(
+            generate("sleep 5", HashMap::new(), Duration::from_millis(100)).await,
+            Err("timed out after 0.1s".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut generators = Generators::new(sender, Duration::from_secs(5));
+
+        // The second run cancels the first one, only its result arrives.
+        generators.run("branch", "sleep 1; echo old", HashMap::new());
+        let id = generators.run("branch", "echo new", HashMap::new());
+
+        let Some(Event::Generated(generated)) = receiver.recv().await else {
+            panic!("expected the result of the generator");
+        };
+        assert_eq!(generated, Generated { id, variable: "branch".into(), result: Ok(vec!["new".into()]) });
+
+        generators.cancel();
+        drop(generators);
+        assert!(receiver.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_kill() {
+        let marker = std::env::temp_dir().join(format!("navu-generator-{}", std::process::id()));
+        let _ = std::fs::remove_file(&marker);
+        let command = format!("(sleep 0.5; touch '{}') & wait", marker.display());
+
+        // What the generator started is killed with it, on a timeout and when the task is aborted.
+        let timeout = Duration::from_millis(100);
+        assert_eq!(generate(&command, HashMap::new(), timeout).await, Err("timed out after 0.1s".into()));
+        let task = tokio::spawn(async move { generate(&command, HashMap::new(), Duration::from_secs(5)).await });
+        tokio::time::sleep(timeout).await;
+        task.abort();
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(!marker.exists());
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// Uses kept for each command, older ones are forgotten.
+const MAX_TIMESTAMPS: usize = 10;
+
+/// Values kept for each variable name, older ones are forgotten.
+const MAX_VALUES: usize = 50;
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Weight of a use depending on its age, recent uses count more.
+const AGE_WEIGHTS: [(u64, f64); 4] = [(4 * DAY, 100.0), (14 * DAY, 70.0), (31 * DAY, 50.0), (90 * DAY, 30.0)];
+const OLD_WEIGHT: f64 = 10.0;
+
+/// Commands selected by the user, stored in `$NAVU_HISTORY` or `navu/history.json` under the XDG data directory.
+///
+/// Commands are identified by their text, so they keep their history when cheat files are moved around.
+/// Values given to the variables are kept by variable name, so commands sharing a name share its values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    commands: HashMap<String, Entry>,
+    #[serde(default)]
+    variables: HashMap<String, Vec<Value>>,
+}
+
+/// Uses of a command.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Times the command was selected since it was first recorded.
+    pub count: u32,
+    /// Seconds since the Unix epoch of the latest uses, oldest first.
+    pub timestamps: Vec<u64>,
+}
+
+/// Value given to a variable of a command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Value {
+    pub value: String,
+    pub command: String,
+    /// Seconds since the Unix epoch of the latest use.
+    pub timestamp: u64,
+}
+
+impl Entry {
+    /// Frequency and recency of the uses, as Firefox does for its address bar: the latest uses are
+    /// weighted by age and scaled up to all the uses of the command.
+    fn frecency(&self, now: u64) -> f64 {
+        if self.timestamps.is_empty() {
+            return 0.0;
+        }
+
+        let weights = self
+            .timestamps
+            .iter()
+            .map(|&timestamp| {
+                let age = now.saturating_sub(timestamp);
+                AGE_WEIGHTS
+                    .iter()
+                    .find(|(limit, _)| age < *limit)
+                    .map_or(OLD_WEIGHT, |(_, weight)| *weight)
+            })
+            .sum::<f64>();
+
+        self.count as f64 * weights / self.timestamps.len() as f64
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+impl History {
+    pub fn path() -> Option<PathBuf> {
+        std::env::var_os("NAVU_HISTORY")
+            .map(PathBuf::from)
+            .or_else(|| dirs::data_dir().map(|data| data.join("navu").join("history.json")))
+    }
+
+    /// History in the default path, empty if there is no file yet.
+    pub fn load() -> Result<Self, Error> {
+        match Self::path() {
+            Some(path) => Self::open(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// History stored in `path`, empty if the file doesn't exist.
+    pub fn open(path: PathBuf) -> Result<Self, Error> {
+        let mut history = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|err| Error::History {
+                path: path.clone(),
+                message: err.to_string(),
+            })?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(err) => return Err(err.into()),
+        };
+
+        history.path = Some(path);
+        Ok(history)
+    }
+
+    /// Empty history stored in `path`, saving it replaces the file without reading it, even if it is broken.
+    pub fn empty(path: PathBuf) -> Self {
+        Self { path: Some(path), ..Self::default() }
+    }
+
+    /// Writes the history back to the file it was read from.
+    pub fn save(&self) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Written aside and renamed, so a crash never leaves half a file.
+        let content = serde_json::to_string(self).map_err(|err| Error::History {
+            path: path.clone(),
+            message: err.to_string(),
+        })?;
+        let temporary = path.with_extension("json.tmp");
+        fs::write(&temporary, content)?;
+        fs::rename(temporary, path)?;
+
+        Ok(())
+    }
+
+    /// Adds a use of the command at `now`.
+    pub fn record(&mut self, command: &str, now: u64) {
+        let entry = self.commands.entry(command.to_string()).or_default();
+        entry.count += 1;
+        entry.timestamps.push(now);
+
+        let extra = entry.timestamps.len().saturating_sub(MAX_TIMESTAMPS);
+        entry.timestamps.drain(..extra);
+    }
+
+    /// Adds the values given to the variables of the command at `now`, empty ones are left out.
+    pub fn record_values(&mut self, command: &str, values: &HashMap<String, String>, now: u64) {
+        for (variable, value) in values.iter().filter(|(_, value)| !value.is_empty()) {
+            let used = self.variables.entry(variable.clone()).or_default();
+            used.retain(|used| used.value != *value || used.command != command);
+            used.push(Value { value: value.clone(), command: command.to_string(), timestamp: now });
+
+            let extra = used.len().saturating_sub(MAX_VALUES);
+            if extra > 0 {
+                used.sort_by_key(|used| used.timestamp);
+                used.drain(..extra);
+            }
+        }
+    }
+
+    /// Values given to the variable, the ones given to this command first, most recent first.
+    pub fn values(&self, command: &str, variable: &str) -> Vec<String> {
+        let mut used = self.variables.get(variable).map(|used| used.iter().collect::<Vec<_>>()).unwrap_or_default();
+        used.sort_by_key(|used| (used.command != command, std::cmp::Reverse(used.timestamp)));
+
+        let mut values = Vec::with_capacity(used.len());
+        for used in used {
+            if !values.contains(&used.value) {
+                values.push(used.value.clone());
+            }
+        }
+        values
+    }
+
+    /// Forgets a value of the variable, or all of them if there is no value, returning how many uses were removed.
+    pub fn forget(&mut self, variable: &str, value: Option<&str>) -> usize {
+        let Some(used) = self.variables.get_mut(variable) else {
+            return 0;
+        };
+
+        let before = used.len();
+        used.retain(|used| value.is_some_and(|value| used.value != value));
+        let removed = before - used.len();
+        if used.is_empty() {
+            self.variables.remove(variable);
+        }
+        removed
+    }
+
+    pub fn get(&self, command: &str) -> Option<&Entry> {
+        self.commands.get(command)
+    }
+
+    /// Score of the command, `0` if it was never selected.
+    pub fn frecency(&self, command: &str, now: u64) -> f64 {
+        self.commands.get(command).map_or(0.0, |entry| entry.frecency(now))
+    }
+
+    /// Forgets the commands not used in the last `days` days, returning how many were removed.
+    pub fn prune(&mut self, days: u64, now: u64) -> usize {
+        let limit = now.saturating_sub(days * DAY);
+        let before = self.commands.len();
+        self.commands
+            .retain(|_, entry| entry.timestamps.last().is_some_and(|&last| last >= limit));
+
+        before - self.commands.len()
+    }
+
+    /// Forgets every command and every value, returning how many commands were removed.
+    pub fn clear(&mut self) -> usize {
+        let removed = self.commands.len();
+        self.commands.clear();
+        self.variables.clear();
+        removed
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    #[test]
+    fn test_frecency() {
+        let mut history = History::default();
+        history.record("git status", NOW);
+        history.record("git status", NOW - DAY);
+        history.record("git log", NOW - 20 * DAY);
+        history.record("git log", NOW - 20 * DAY);
+        history.record("git log", NOW - 20 * DAY);
+
+        assert_eq!(history.frecency("git status", NOW), 200.0);
+        assert_eq!(history.frecency("git log", NOW), 150.0);
+        assert_eq!(history.frecency("git push", NOW), 0.0);
+
+        // Only the latest uses are kept, but all of them are counted.
+        for _ in 0..20 {
+            history.record("ls", NOW - 100 * DAY);
+        }
+        history.record("ls", NOW);
+        let entry = history.get("ls").unwrap();
+        assert_eq!(entry.count, 21);
+        assert_eq!(entry.timestamps.len(), MAX_TIMESTAMPS);
+        assert_eq!(entry.timestamps.last(), Some(&NOW));
+    }
+
+    #[test]
+    fn test_prune() {
+        let mut history = History::default();
+        history.record("git status", NOW - 100 * DAY);
+        history.record("git status", NOW - DAY);
+        history.record("git log", NOW - 100 * DAY);
+
+        assert_eq!(history.prune(30, NOW), 1);
+        assert!(history.get("git status").is_some());
+        assert!(history.get("git log").is_none());
+
+        assert_eq!(history.clear(), 1);
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn test_values() {
+        let mut history = History::default();
+        let values = |pairs: &[(&str, &str)]| {
+            pairs.iter().map(|(variable, value)| (variable.to_string(), value.to_string())).collect::<HashMap<_, _>>()
+        };
+        history.record_values("kubectl get pods -n <namespace>", &values(&[("namespace", "prod")]), NOW - 2 * DAY);
+        history.record_values("kubectl get pods -n <namespace>", &values(&[("namespace", "dev")]), NOW - DAY);
+        history.record_values("kubectl logs -n <namespace> <pod>", &values(&[("namespace", "staging"), ("pod", "")]), NOW);
+        history.record_values("kubectl get pods -n <namespace>", &values(&[("namespace", "prod")]), NOW - 3 * DAY);
+
+        // Values of this command first, then the ones shared by other commands.
+        assert_eq!(history.values("kubectl get pods -n <namespace>", "namespace"), vec!["dev", "prod", "staging"]);
+        assert_eq!(history.values("kubectl logs -n <namespace> <pod>", "namespace"), vec!["staging", "dev", "prod"]);
+        assert!(history.values("kubectl logs -n <namespace> <pod>", "pod").is_empty());
+
+        assert_eq!(history.forget("namespace", Some("dev")), 1);
+        assert_eq!(history.values("ls", "namespace"), vec!["staging", "prod"]);
+        assert_eq!(history.forget("namespace", None), 2);
+        assert!(history.values("ls", "namespace").is_empty());
+        assert_eq!(history.forget("namespace", None), 0);
+    }
+
+    #[test]
+    fn test_save() {
+        let path = std::env::temp_dir().join(format!("navu-history-{}", std::process::id())).join("history.json");
+
+        let mut history = History::open(path.clone()).unwrap();
+        assert!(history.is_empty());
+        history.record("git status", NOW);
+        history.save().unwrap();
+
+        let history = History::open(path.clone()).unwrap();
+        assert_eq!(history.get("git status"), Some(&Entry { count: 1, timestamps: vec![NOW] }));
+
+        std::fs::write(&path, "{").unwrap();
+        assert!(matches!(History::open(path.clone()), Err(Error::History { .. })));
+
+        // A broken file can still be replaced by an empty history.
+        History::empty(path.clone()).save().unwrap();
+        assert!(History::open(path.clone()).unwrap().is_empty());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
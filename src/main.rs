@@ -1,35 +1,90 @@
+use std::fs;
 use std::io;
+use std::process::ExitCode;
 
+use clap::Parser;
 use ratatui::{backend::CrosstermBackend, Terminal};
 use crate::{
     app::{App, AppResult},
-    event::EventHandler,
+    cli::{Cli, Commands},
+    config::Config,
+    history::History,
+    event::{Event, EventHandler},
+    search::CommandMatcher,
     tui::Tui,
     error::Error
 };
 
+pub mod ansi;
 pub mod app;
+pub mod cheat;
+pub mod cli;
+pub mod completion;
+pub mod config;
+pub mod danger;
 pub mod event;
+pub mod execution;
+pub mod generator;
+pub mod pty;
 pub mod tui;
 pub mod ui;
 pub mod error;
+pub mod history;
 pub mod search;
+pub mod shell;
+pub mod syntax;
 pub mod table;
+pub mod template;
+pub mod widget;
 
 pub(crate) mod fuzzy;
 
 #[tokio::main]
-async fn main() -> AppResult<()> {
-    // Create an application.
-    let mut app = App::new();
+async fn main() -> AppResult<ExitCode> {
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Commands::History { action }) => {
+            action.run()?;
+            return Ok(ExitCode::SUCCESS);
+        },
+        Some(Commands::Widget { shell }) => {
+            print!("{}", shell.script());
+            return Ok(ExitCode::SUCCESS);
+        },
+        None => {},
+    }
+
+    let config = Config::load()?;
+    // A broken history doesn't stop navu, it starts empty and the file is left alone until it is reset.
+    let (history, history_error) = match History::load() {
+        Ok(history) => (history, None),
+        Err(err) => (History::default(), Some(err)),
+    };
 
-    // Initialize the terminal user interface.
-    let backend = CrosstermBackend::new(io::stdout());
+    // Initialize the terminal user interface, stdout is kept for the command chosen.
+    let backend = CrosstermBackend::new(io::stderr());
     let terminal = Terminal::new(backend)?;
     let events = EventHandler::new(250);
     let mut tui = Tui::new(terminal, events);
     tui.init()?;
 
+    // Create an application.
+    let mut app = App::new(&config, history, tui.events.sender());
+    app.select_1 = cli.select_1;
+    app.exit_0 = cli.exit_0;
+    app.table_state.status = history_error.map(|err| format!("{}, run `navu history reset` to start over", err));
+    if let Some(query) = cli.query.or_else(|| (!cli.words.is_empty()).then(|| cli.words.join(" "))) {
+        app.set_query(&query);
+    }
+
+    // Load the catalog in the background, the commands show up as they are parsed.
+    let loader = CommandMatcher::new(cheat::default_path(), app.searcher.injector());
+    let sender = tui.events.sender();
+    tokio::spawn(async move {
+        let errors = loader.load_commands().await;
+        let _ = sender.send(Event::Loaded(errors));
+    });
+
     // Start the main loop.
     while app.running {
         // Render the user interface.
@@ -46,5 +101,15 @@ async fn main() -> AppResult<()> {
 
     // Exit the user interface.
     tui.exit()?;
-    Ok(())
+
+    // The command goes to the shell wrapper, cancelling leaves it nothing.
+    let Some(command) = app.accepted else {
+        return Ok(ExitCode::FAILURE);
+    };
+    match cli.output {
+        Some(path) => fs::write(path, command)?,
+        None => println!("{}", command),
+    }
+
+    Ok(ExitCode::SUCCESS)
 }
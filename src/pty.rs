@@ -0,0 +1,232 @@
+//! Pseudo-terminal running the interactive commands, like `htop` or `git rebase -i`, inside navu.
+//!
+//! The output of the command is parsed by a VT100 emulator into a screen the interface draws, the keys
+//! are written back as the bytes a terminal would send.
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use portable_pty::{ChildKiller, CommandBuilder, MasterPty, PtySize};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::error::Error;
+use crate::event::Event;
+use crate::shell::Shell;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Lines kept above the screen, the emulator needs them to scroll.
+const SCROLLBACK: usize = 1000;
+
+/// Progress of the command in the terminal, sent as an [`Event::Pty`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PtyEvent {
+    /// The screen changed.
+    Output { id: u64 },
+    /// The command finished, its terminal is closed.
+    Exited { id: u64, status: String, success: bool },
+}
+
+/// Command running in a pseudo-terminal, it is killed when dropped.
+pub struct Pty {
+    pub id: u64,
+    /// Command as given to the shell.
+    pub command: String,
+    parser: Arc<Mutex<vt100::Parser>>,
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+}
+
+impl fmt::Debug for Pty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pty").field("id", &self.id).field("command", &self.command).finish()
+    }
+}
+
+impl Pty {
+    /// Runs the command with the shell in a terminal of the size given, as rows and columns.
+    pub fn spawn(command: &str, shell: Shell, (rows, cols): (u16, u16), sender: UnboundedSender<Event>) -> Result<Self, Error> {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let size = PtySize { rows, cols, pixel_width: 0, pixel_height: 0 };
+        let pair = portable_pty::native_pty_system().openpty(size).map_err(|err| Error::Terminal(err.to_string()))?;
+
+        let mut builder = CommandBuilder::new(shell.program());
+        builder.arg(shell.command_flag());
+        builder.arg(command);
+        builder.env("TERM", "xterm-256color");
+        if let Ok(folder) = std::env::current_dir() {
+            builder.cwd(folder);
+        }
+
+        let mut child = pair.slave.spawn_command(builder).map_err(|err| Error::Terminal(err.to_string()))?;
+        // The reader only ends once every handle to the other side is closed, ours too.
+        drop(pair.slave);
+
+        let killer = child.clone_killer();
+        let mut reader = pair.master.try_clone_reader().map_err(|err| Error::Terminal(err.to_string()))?;
+        let writer = pair.master.take_writer().map_err(|err| Error::Terminal(err.to_string()))?;
+        let parser = Arc::new(Mutex::new(vt100::Parser::new(rows, cols, SCROLLBACK)));
+
+        let screen = parser.clone();
+        std::thread::spawn(move || {
+            let mut buffer = [0; 4096];
+            // Linux fails with EIO instead of reading nothing once the command exits.
+            while let Ok(read @ 1..) = reader.read(&mut buffer) {
+                screen.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).process(&buffer[..read]);
+                if sender.send(Event::Pty(PtyEvent::Output { id })).is_err() {
+                    return;
+                }
+            }
+
+            let (status, success) = match child.wait() {
+                Ok(status) => (status.to_string(), status.success()),
+                Err(err) => (err.to_string(), false),
+            };
+            let _ = sender.send(Event::Pty(PtyEvent::Exited { id, status, success }));
+        });
+
+        Ok(Self { id, command: command.to_string(), parser, master: pair.master, writer, killer })
+    }
+
+    /// Emulator with the screen of the command.
+    pub fn parser(&self) -> MutexGuard<'_, vt100::Parser> {
+        self.parser.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Changes the size of the terminal, as rows and columns, if it is a different one.
+    pub fn resize(&mut self, (rows, cols): (u16, u16)) {
+        let mut parser = self.parser();
+        if parser.screen().size() == (rows, cols) {
+            return;
+        }
+
+        parser.set_size(rows, cols);
+        let _ = self.master.resize(PtySize { rows, cols, pixel_width: 0, pixel_height: 0 });
+    }
+
+    /// Sends the key to the command.
+    pub fn key(&mut self, key: KeyEvent) {
+        let application_cursor = self.parser().screen().application_cursor();
+        let bytes = key_bytes(key, application_cursor);
+        if !bytes.is_empty() {
+            let _ = self.writer.write_all(&bytes).and_then(|_| self.writer.flush());
+        }
+    }
+
+    /// Hangs up the command, its exit is still sent.
+    pub fn kill(&mut self) {
+        let _ = self.killer.kill();
+    }
+}
+
+impl Drop for Pty {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+/// Bytes a terminal sends for the key, the arrows change with the application cursor mode.
+pub fn key_bytes(key: KeyEvent, application_cursor: bool) -> Vec<u8> {
+    let arrow = |code: u8| match application_cursor {
+        true => vec![0x1b, b'O', code],
+        false => vec![0x1b, b'[', code],
+    };
+    let tilde = |code: &str| format!("\x1b[{}~", code).into_bytes();
+
+    let mut bytes = match key.code {
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => match c {
+            'a'..='z' | 'A'..='Z' => vec![c.to_ascii_lowercase() as u8 & 0x1f],
+            ' ' | '@' | '2' => vec![0],
+            '[' | '3' => vec![0x1b],
+            '\\' | '4' => vec![0x1c],
+            ']' | '5' => vec![0x1d],
+            '^' | '6' => vec![0x1e],
+            '_' | '-' | '7' => vec![0x1f],
+            '?' | '8' => vec![0x7f],
+            c => c.to_string().into_bytes(),
+        },
+        KeyCode::Char(c) => c.to_string().into_bytes(),
+        KeyCode::Enter => vec![b'\r'],
+        KeyCode::Backspace => vec![0x7f],
+        KeyCode::Tab => vec![b'\t'],
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Esc => vec![0x1b],
+        KeyCode::Up => arrow(b'A'),
+        KeyCode::Down => arrow(b'B'),
+        KeyCode::Right => arrow(b'C'),
+        KeyCode::Left => arrow(b'D'),
+        KeyCode::Home => arrow(b'H'),
+        KeyCode::End => arrow(b'F'),
+        KeyCode::Insert => tilde("2"),
+        KeyCode::Delete => tilde("3"),
+        KeyCode::PageUp => tilde("5"),
+        KeyCode::PageDown => tilde("6"),
+        KeyCode::F(n @ 1..=4) => vec![0x1b, b'O', b'P' + n - 1],
+        KeyCode::F(n @ 5..=12) => tilde(["15", "17", "18", "19", "20", "21", "23", "24"][n as usize - 5]),
+        _ => vec![],
+    };
+
+    // Alt is sent as an escape before the key.
+    if key.modifiers.contains(KeyModifiers::ALT) && !bytes.is_empty() {
+        bytes.insert(0, 0x1b);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_key_bytes() {
+        for (code, modifiers, application_cursor, bytes) in [
+            (KeyCode::Char('q'), KeyModifiers::NONE, false, &b"q"[..]),
+            (KeyCode::Char('é'), KeyModifiers::NONE, false, "é".as_bytes()),
+            (KeyCode::Char('c'), KeyModifiers::CONTROL, false, b"\x03"),
+            (KeyCode::Char('D'), KeyModifiers::CONTROL | KeyModifiers::SHIFT, false, b"\x04"),
+            (KeyCode::Char('b'), KeyModifiers::ALT, false, b"\x1bb"),
+            (KeyCode::Enter, KeyModifiers::NONE, false, b"\r"),
+            (KeyCode::Up, KeyModifiers::NONE, false, b"\x1b[A"),
+            (KeyCode::Up, KeyModifiers::NONE, true, b"\x1bOA"),
+            (KeyCode::PageDown, KeyModifiers::NONE, false, b"\x1b[6~"),
+            (KeyCode::F(1), KeyModifiers::NONE, false, b"\x1bOP"),
+            (KeyCode::F(10), KeyModifiers::NONE, false, b"\x1b[21~"),
+        ] {
+            assert_eq!(key_bytes(KeyEvent::new(code, modifiers), application_cursor), bytes, "{:?} {:?}", code, modifiers);
+        }
+    }
+
+    #[test]
+    fn test_pty() {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut pty = Pty::spawn("read line; printf '\\033[1;31m%s\\033[0m' \"$line\"", Shell::Sh, (5, 20), sender).unwrap();
+        for c in "hi".chars() {
+            pty.key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        pty.key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let exited = loop {
+            assert!(Instant::now() < deadline, "the command didn't exit");
+            match receiver.try_recv() {
+                Ok(Event::Pty(PtyEvent::Exited { id, success, .. })) if id == pty.id => break success,
+                Ok(_) => {},
+                Err(_) => std::thread::sleep(Duration::from_millis(10)),
+            }
+        };
+        assert!(exited);
+
+        // The line typed is echoed by the terminal, then printed in bold red.
+        let parser = pty.parser();
+        let screen = parser.screen();
+        assert_eq!(screen.contents(), "hi\nhi");
+        let cell = screen.cell(1, 0).unwrap();
+        assert!(cell.bold());
+        assert_eq!(cell.fgcolor(), vt100::Color::Idx(1));
+    }
+}
@@ -1,49 +1,803 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use nucleo::pattern::{CaseMatching, Normalization, Pattern};
+use nucleo::{Config, Injector, Matcher, Nucleo, Utf32Str, Utf32String};
+use tokio::fs;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 
+use crate::cheat;
+use crate::config::SearchConfig;
+use crate::error::Error;
+use crate::fuzzy::prelude::*;
+use crate::fuzzy::Phonetic;
+use crate::history::{self, History};
+use crate::table::{Identity, Table};
+use crate::ui::commands::Command;
 
-// pub struct CommandMatcher {
-//     folder: PathBuf, 
-//     injector: Injector<String>
-// }
-
-// impl CommandMatcher {
-//     pub fn new(folder: PathBuf, injector: Injector<String>) -> Self {
-//         Self { folder, injector }
-//     }
-
-//     pub async fn load_commands(&self) -> Result<(), Error> {
-//         let mut entries = fs::read_dir(&self.folder).await?;
-
-//         while let Ok(Some(entry)) = entries.next_entry().await {
-//             let entry = entry.path();
-//             let content = fs::read_to_string(&entry).await?;
-
-//             self.injector.push(content, 
-//                 |value: String, s: &mut Vec<Utf32String>| {
-//                     let mut value = Utf32String::Ascii(value.clone().into());
-//                     s.push(value);
-//                 });
-//         }
-
-//         Ok(())
-//     }
-// }
-
-// pub struct MatcherCommands {
-//     nucleo: Nucleo<String>,
-//     pattern: Option<String>,
-// }
-
-// // TODO: `Notify` will call the table to update the view.
-// impl MatcherCommands {
-//     pub fn new() -> Self {
-//         let nucleo: Nucleo<String> = Nucleo::new(
-//             Config::DEFAULT,
-//             Arc::new(move || {}),
-//             Some(3),
-//             1,
-//         );
-
-//         Self { nucleo, pattern: None }
-//     }
-// }
\ No newline at end of file
+pub mod query;
+
+pub use query::Query;
+
+/// Files with ignore rules, read in every folder of the catalog. They use the `.gitignore` syntax.
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".navuignore"];
+
+/// Maximum number of cheat files read at the same time.
+const MAX_OPEN_FILES: usize = 64;
+
+/// How long the search thread waits for the worker threads on each tick, in milliseconds.
+const TICK_TIMEOUT: u64 = 10;
+
+/// Command in the matcher, with what the phonetic search needs of it.
+struct Item {
+    /// Shared with the hits, so publishing results doesn't copy the commands.
+    command: Arc<Command>,
+    phonetic: Phonetic,
+    /// Codes of the words of the command, its description and its tags, encoded once when it is added.
+    codes: Vec<String>,
+}
+
+/// Text of the command used by the matcher, the fields separated by a space.
+fn matched_text(command: &Command) -> String {
+    format!("{} {} {}", command.command, command.tags.join(" "), command.description)
+}
+
+fn fill_columns(item: &Item, columns: &mut [Utf32String]) {
+    columns[0] = matched_text(&item.command).into();
+}
+
+thread_local! {
+    /// Finds the matched graphemes of the hits shown, in the thread drawing them.
+    static MATCHER: RefCell<Matcher> = RefCell::new(Matcher::new(Config::DEFAULT));
+}
+
+/// How a command matched the query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    Fuzzy,
+    /// Some word of the command sounds like each word of the query.
+    Phonetic,
+}
+
+/// Command found by the search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hit {
+    pub command: Arc<Command>,
+    pub kind: MatchKind,
+    /// Text the command was matched against, shared by the hits of a result set.
+    query: Arc<str>,
+}
+
+impl Hit {
+    /// Sorted positions of the graphemes that matched the query, in the text built by `matched_text`.
+    ///
+    /// Only found for the hits shown, a result set can hold the whole catalog.
+    pub fn indices(&self) -> Vec<u32> {
+        if self.kind != MatchKind::Fuzzy {
+            return vec![];
+        }
+
+        let pattern = Pattern::parse(&self.query, CaseMatching::Smart, Normalization::Smart);
+        let text = matched_text(&self.command);
+        let mut buffer = vec![];
+        let mut indices = vec![];
+        MATCHER.with_borrow_mut(|matcher| pattern.indices(Utf32Str::new(&text, &mut buffer), matcher, &mut indices));
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+}
+
+impl Identity for Hit {
+    fn is(&self, other: &Self) -> bool {
+        self.command.is(&other.command)
+    }
+}
+
+/// Ignore rules of a folder and all its ancestors inside the catalog, the deepest folder last.
+#[derive(Clone, Default)]
+struct IgnoreRules(Vec<Arc<Gitignore>>);
+
+impl IgnoreRules {
+    fn ignored(&self, path: &Path, is_dir: bool) -> bool {
+        // Hidden entries are never part of the catalog.
+        if path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')) {
+            return true;
+        }
+
+        for rules in self.0.iter().rev() {
+            match rules.matched(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {},
+            }
+        }
+
+        false
+    }
+
+    /// Rules for `folder`, which is a child of the folder owning these rules.
+    async fn enter(&self, folder: &Path) -> Result<Self, Error> {
+        let mut builder = GitignoreBuilder::new(folder);
+        let mut found = false;
+
+        for name in IGNORE_FILES {
+            let path = folder.join(name);
+            let content = match fs::read_to_string(&path).await {
+                Ok(content) => content,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            for line in content.lines() {
+                builder.add_line(Some(path.clone()), line)?;
+            }
+            found = true;
+        }
+
+        let mut rules = self.clone();
+        if found {
+            rules.0.push(Arc::new(builder.build()?));
+        }
+
+        Ok(rules)
+    }
+}
+
+/// Phonetic algorithm of each command, and the encoders to use them.
+struct Phonetics {
+    /// Algorithm used when neither the catalog nor the language of the command choose one.
+    default: Phonetic,
+    /// Algorithms chosen for some folders of the catalog, deepest folders first.
+    catalogs: Vec<(PathBuf, Phonetic)>,
+    detect_language: bool,
+    encoders: HashMap<Phonetic, Box<dyn PhoneticEncoder>>,
+}
+
+impl Phonetics {
+    fn new(config: &SearchConfig) -> Self {
+        let mut catalogs = config
+            .catalogs
+            .iter()
+            .map(|(folder, phonetic)| (folder.clone(), *phonetic))
+            .collect::<Vec<_>>();
+        catalogs.sort_by_key(|(folder, _)| std::cmp::Reverse(folder.components().count()));
+
+        Self {
+            default: config.phonetic,
+            catalogs,
+            detect_language: config.detect_language,
+            encoders: Phonetic::ALL.into_iter().map(|phonetic| (phonetic, phonetic.encoder())).collect(),
+        }
+    }
+
+    /// Algorithm for the command, from the folder it was read from or the language of its description.
+    fn phonetic_for(&self, command: &Command) -> Phonetic {
+        let catalog = command.source.as_ref().and_then(|source| {
+            self.catalogs
+                .iter()
+                .find(|(folder, _)| source.path.starts_with(folder))
+                .map(|(_, phonetic)| *phonetic)
+        });
+
+        catalog
+            .or_else(|| {
+                self.detect_language
+                    .then(|| detect(&command.description))
+                    .flatten()
+                    .map(Phonetic::for_language)
+            })
+            .unwrap_or(self.default)
+    }
+
+    /// Command ready for the matcher, with the codes of its words.
+    fn item(&self, command: Command) -> Item {
+        let phonetic = self.phonetic_for(&command);
+        let texts = [command.command.as_str(), command.description.as_str()];
+        let texts = texts.into_iter().chain(command.tags.iter().map(String::as_str));
+        let codes = encode_words(texts, self.encoders[&phonetic].as_ref());
+        Item { command: Arc::new(command), phonetic, codes }
+    }
+}
+
+/// Adds commands to the matcher from other threads, their words are encoded for the phonetic search
+/// on the way, so the matcher doesn't encode the whole catalog on every search.
+#[derive(Clone)]
+pub struct CommandInjector {
+    injector: Injector<Item>,
+    phonetics: Arc<Phonetics>,
+}
+
+impl CommandInjector {
+    pub fn push(&self, command: Command) {
+        self.injector.push(self.phonetics.item(command), fill_columns);
+    }
+
+    /// Number of commands added so far.
+    pub fn injected_items(&self) -> u32 {
+        self.injector.injected_items()
+    }
+}
+
+/// Loads the commands of a catalog folder into the matcher.
+pub struct CommandMatcher {
+    folder: PathBuf,
+    injector: CommandInjector,
+}
+
+impl CommandMatcher {
+    pub fn new(folder: PathBuf, injector: CommandInjector) -> Self {
+        Self { folder, injector }
+    }
+
+    /// Walks the catalog recursively, skipping ignored entries, and pushes the commands of every
+    /// cheat file into the matcher as soon as the file is parsed. Files are read in parallel.
+    ///
+    /// A broken file doesn't stop the load, the errors of every file are returned at the end.
+    pub async fn load_commands(&self) -> Vec<Error> {
+        let mut errors = vec![];
+        let semaphore = Arc::new(Semaphore::new(MAX_OPEN_FILES));
+        let mut folders = JoinSet::new();
+        let mut files = JoinSet::new();
+        let visited = Arc::new(Mutex::new(HashSet::new()));
+
+        match fs::metadata(&self.folder).await {
+            Ok(metadata) if metadata.is_dir() => {
+                folders.spawn(Self::read_folder(self.folder.clone(), IgnoreRules::default(), visited.clone()));
+            },
+            Ok(_) => {
+                files.spawn(Self::load_file(self.folder.clone(), self.injector.clone(), semaphore.clone()));
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {},
+            Err(err) => errors.push(err.into()),
+        }
+
+        while let Some(listing) = folders.join_next().await {
+            let (subfolders, cheats) = match listing {
+                Ok(Ok(listing)) => listing,
+                Ok(Err(err)) => {
+                    errors.push(err);
+                    continue;
+                },
+                Err(err) => {
+                    errors.push(Error::Custom(err.to_string()));
+                    continue;
+                },
+            };
+
+            for (folder, rules) in subfolders {
+                folders.spawn(Self::read_folder(folder, rules, visited.clone()));
+            }
+
+            for path in cheats {
+                files.spawn(Self::load_file(path, self.injector.clone(), semaphore.clone()));
+            }
+        }
+
+        while let Some(loaded) = files.join_next().await {
+            match loaded {
+                Ok(Ok(())) => {},
+                Ok(Err(err)) => errors.push(err),
+                Err(err) => errors.push(Error::Custom(err.to_string())),
+            }
+        }
+
+        errors
+    }
+
+    /// Lists a folder, returning its subfolders, with their ignore rules, and its cheat files.
+    ///
+    /// `visited` has the canonical paths of the folders listed, a folder reached again through a link,
+    /// like a link to a parent, is skipped.
+    async fn read_folder(
+        folder: PathBuf,
+        parent: IgnoreRules,
+        visited: Arc<Mutex<HashSet<PathBuf>>>,
+    ) -> Result<(Vec<(PathBuf, IgnoreRules)>, Vec<PathBuf>), Error> {
+        let canonical = fs::canonicalize(&folder).await?;
+        if !visited.lock().unwrap().insert(canonical) {
+            return Ok((vec![], vec![]));
+        }
+
+        let rules = parent.enter(&folder).await?;
+        let mut entries = fs::read_dir(&folder).await?;
+        let mut subfolders = vec![];
+        let mut cheats = vec![];
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            // Follow symlinks, catalogs are often links to other repositories.
+            let is_dir = fs::metadata(&path).await.is_ok_and(|metadata| metadata.is_dir());
+
+            if rules.ignored(&path, is_dir) {
+                continue;
+            }
+
+            if is_dir {
+                subfolders.push((path, rules.clone()));
+            } else if cheat::is_cheat(&path) {
+                cheats.push(path);
+            }
+        }
+
+        Ok((subfolders, cheats))
+    }
+
+    async fn load_file(path: PathBuf, injector: CommandInjector, semaphore: Arc<Semaphore>) -> Result<(), Error> {
+        let content = {
+            let _permit = semaphore
+                .acquire()
+                .await
+                .map_err(|err| Error::Custom(err.to_string()))?;
+            fs::read_to_string(&path).await?
+        };
+
+        for command in cheat::parse(&path, &content)? {
+            injector.push(command);
+        }
+
+        Ok(())
+    }
+}
+
+/// Fuzzy matcher over all the loaded commands, matching runs in the nucleo thread pool.
+pub struct MatcherCommands {
+    nucleo: Nucleo<Item>,
+    /// Scores the matched items, nucleo only gives them in order.
+    matcher: Matcher,
+    query: Query,
+    /// Set once `notify` has been called, until the next tick collects the results.
+    notified: Arc<AtomicBool>,
+    /// Shared with the injectors, which encode the commands as they are added.
+    phonetics: Arc<Phonetics>,
+    /// Below this number of fuzzy matches, commands that sound like the query are added to the results.
+    phonetic_min_results: usize,
+    /// Selected commands, the ones used often and recently go first.
+    history: History,
+    fuzzy_weight: f64,
+    frecency_weight: f64,
+}
+
+impl MatcherCommands {
+    /// `notify` is called when the worker threads have new results, it is debounced
+    /// so it will not be called again until [`MatcherCommands::tick`] runs.
+    pub fn new(config: &SearchConfig, notify: impl Fn() + Send + Sync + 'static) -> Self {
+        let notified = Arc::new(AtomicBool::new(false));
+        let pending = notified.clone();
+
+        let nucleo: Nucleo<Item> = Nucleo::new(
+            Config::DEFAULT,
+            Arc::new(move || {
+                if !pending.swap(true, Ordering::AcqRel) {
+                    notify();
+                }
+            }),
+            None,
+            1,
+        );
+
+        Self {
+            nucleo,
+            matcher: Matcher::new(Config::DEFAULT),
+            query: Query::default(),
+            notified,
+            phonetics: Arc::new(Phonetics::new(config)),
+            phonetic_min_results: config.phonetic_min_results,
+            history: History::default(),
+            fuzzy_weight: config.fuzzy_weight,
+            frecency_weight: config.frecency_weight,
+        }
+    }
+
+    /// Replaces the history used to order the results, it takes effect in the next [`MatcherCommands::results`].
+    pub fn set_history(&mut self, history: History) {
+        self.history = history;
+    }
+
+    /// Updates the query the commands are matched against, results are available after some ticks.
+    pub fn search(&mut self, query: &Query) {
+        if query.text != self.query.text {
+            // Extending the text can only narrow the results, so nucleo only needs to look at the last matches.
+            let append = query.text.starts_with(&self.query.text);
+            self.nucleo.pattern.reparse(0, &query.text, CaseMatching::Smart, Normalization::Smart, append);
+        }
+        self.query = query.clone();
+    }
+
+    /// Handle to add commands to the matcher from other threads.
+    pub fn injector(&self) -> CommandInjector {
+        CommandInjector { injector: self.nucleo.injector(), phonetics: self.phonetics.clone() }
+    }
+
+    /// Collects the work done by the worker threads, waiting at most `timeout` milliseconds for them.
+    pub fn tick(&mut self, timeout: u64) -> nucleo::Status {
+        self.notified.store(false, Ordering::Release);
+        self.nucleo.tick(timeout)
+    }
+
+    /// Commands matched in the last completed run, best first.
+    ///
+    /// When there are only a few fuzzy matches, the commands that sound like the query
+    /// are added after them, so misspelled queries still find something.
+    pub fn results(&mut self) -> Vec<Hit> {
+        let snapshot = self.nucleo.snapshot();
+        let pattern = self.nucleo.pattern.column_pattern(0);
+        let query: Arc<str> = self.query.text.as_str().into();
+        let hit = |command: &Arc<Command>, kind| Hit { command: command.clone(), kind, query: query.clone() };
+
+        let scored = snapshot
+            .matched_items(..)
+            .filter(|item| self.query.matches(&item.data.command))
+            .map(|item| {
+                let score = pattern.score(item.matcher_columns[0].slice(..), &mut self.matcher).unwrap_or(0);
+                (score, hit(&item.data.command, MatchKind::Fuzzy))
+            })
+            .collect::<Vec<_>>();
+        let mut hits = self.rank(scored);
+
+        if hits.len() >= self.phonetic_min_results {
+            return hits;
+        }
+
+        // The query is encoded once for each algorithm the commands need.
+        let mut queries = HashMap::new();
+        let fuzzy = hits.len();
+
+        for index in 0..snapshot.item_count() {
+            let Some(item) = snapshot.get_item(index).map(|item| item.data) else {
+                continue;
+            };
+            let command = &item.command;
+            if !self.query.matches(command) || hits[..fuzzy].iter().any(|hit| hit.command.is(command)) {
+                continue;
+            }
+
+            let encoder = self.phonetics.encoders[&item.phonetic].as_ref();
+            let query = queries
+                .entry(item.phonetic)
+                .or_insert_with(|| PhoneticQuery::new(&self.query.text, encoder));
+            let Some(query) = query else {
+                continue;
+            };
+
+            if query.matches(&item.codes) {
+                hits.push(hit(command, MatchKind::Phonetic));
+            }
+        }
+
+        hits
+    }
+
+    /// Orders the fuzzy hits by their score blended with the frecency of the command, both relative
+    /// to the best of the results. Without a text to match, only the frecency counts.
+    fn rank(&self, scored: Vec<(u32, Hit)>) -> Vec<Hit> {
+        let now = history::now();
+        let mut ranked = scored
+            .into_iter()
+            .map(|(score, hit)| (score as f64, self.history.frecency(&hit.command.command, now), hit))
+            .collect::<Vec<_>>();
+
+        let best_score = ranked.iter().map(|(score, _, _)| *score).fold(0.0, f64::max);
+        let best_frecency = ranked.iter().map(|(_, frecency, _)| *frecency).fold(0.0, f64::max);
+        let relative = |value: f64, best: f64| if best > 0.0 { value / best } else { 0.0 };
+
+        let blend = |score: f64, frecency: f64| {
+            if self.query.text.is_empty() {
+                frecency
+            } else {
+                self.fuzzy_weight * relative(score, best_score) + self.frecency_weight * relative(frecency, best_frecency)
+            }
+        };
+
+        // Stable, so ties keep the order given by nucleo.
+        ranked.sort_by(|(score, frecency, _), (other_score, other_frecency, _)| {
+            blend(*other_score, *other_frecency).total_cmp(&blend(*score, *frecency))
+        });
+
+        ranked.into_iter().map(|(_, _, hit)| hit).collect()
+    }
+}
+
+impl std::fmt::Debug for MatcherCommands {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let snapshot = self.nucleo.snapshot();
+        f.debug_struct("MatcherCommands")
+            .field("query", &self.query)
+            .field("items", &snapshot.item_count())
+            .field("matched", &snapshot.matched_item_count())
+            .finish()
+    }
+}
+
+/// Called with the results once every command injected so far is matched.
+type Settled = Box<dyn FnOnce(&[Hit]) + Send>;
+
+enum Message {
+    Query(Query),
+    History(History),
+    Settle(Settled),
+    /// The worker threads have new results.
+    Notify,
+    Stop,
+}
+
+/// Handle to the search thread, which owns the [`MatcherCommands`] and publishes every
+/// new result set to the shared [`Table`]. The thread stops when the handle is dropped.
+pub struct Searcher {
+    messages: mpsc::Sender<Message>,
+    injector: CommandInjector,
+}
+
+impl Searcher {
+    /// Starts the search thread, `notify` is called after a new result set is published.
+    pub fn spawn(table: Arc<Table<Hit>>, config: &SearchConfig, notify: impl Fn() + Send + 'static) -> Self {
+        let (messages, receiver) = mpsc::channel();
+        let wake = messages.clone();
+        let mut matcher = MatcherCommands::new(config, move || {
+            let _ = wake.send(Message::Notify);
+        });
+        let injector = matcher.injector();
+
+        thread::spawn(move || {
+            let mut running = false;
+            let mut settling: Vec<Settled> = vec![];
+
+            loop {
+                // While the workers are busy keep ticking, otherwise sleep until something happens.
+                let message = if running {
+                    receiver.recv_timeout(Duration::from_millis(TICK_TIMEOUT)).ok()
+                } else {
+                    receiver.recv().ok()
+                };
+
+                // Only the last query matters, older ones are already outdated.
+                let mut outdated = false;
+                for message in message.into_iter().chain(receiver.try_iter()) {
+                    match message {
+                        Message::Query(query) => {
+                            matcher.search(&query);
+                            outdated = true;
+                        },
+                        Message::History(history) => {
+                            matcher.set_history(history);
+                            outdated = true;
+                        },
+                        Message::Settle(settled) => settling.push(settled),
+                        Message::Notify => {},
+                        Message::Stop => return,
+                    }
+                }
+
+                let status = matcher.tick(TICK_TIMEOUT);
+                running = status.running;
+
+                // Filters and history are applied to the results, nucleo doesn't see them change.
+                if status.changed || outdated {
+                    table.publish(matcher.results());
+                    notify();
+                }
+
+                if !running {
+                    let hits = table.load();
+                    for settled in settling.drain(..) {
+                        settled(&hits.rows);
+                    }
+                }
+            }
+        });
+
+        Self { messages, injector }
+    }
+
+    /// Matches the commands against a new query, the results are published to the table.
+    pub fn search(&self, query: Query) {
+        let _ = self.messages.send(Message::Query(query));
+    }
+
+    /// Orders the results with a new history, after a command was selected.
+    pub fn set_history(&self, history: History) {
+        let _ = self.messages.send(Message::History(history));
+    }
+
+    /// Calls `settled` with the results once the commands injected so far and the last query are matched.
+    pub fn settle(&self, settled: impl FnOnce(&[Hit]) + Send + 'static) {
+        let _ = self.messages.send(Message::Settle(Box::new(settled)));
+    }
+
+    /// Handle to add commands to the matcher from other threads.
+    pub fn injector(&self) -> CommandInjector {
+        self.injector.clone()
+    }
+}
+
+impl std::fmt::Debug for Searcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Searcher")
+            .field("injected", &self.injector.injected_items())
+            .finish()
+    }
+}
+
+impl Drop for Searcher {
+    fn drop(&mut self) {
+        let _ = self.messages.send(Message::Stop);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::commands::Source;
+
+    fn catalog() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("navu-catalog-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+
+        write("git.cheat", "% git\n\n# Status\ngit status\n");
+        write("docker/compose.cheat", "% docker\n\n# Up\ndocker compose up\n\n# Down\ndocker compose down\n");
+        write("docker/old/legacy.cheat", "% docker\n\n# Legacy\ndocker-compose up\n");
+        write("docker/.navuignore", "old/\n");
+        write(".hidden/secret.cheat", "% hidden\n\nsecret\n");
+        write("broken.cheat", "$ nope\n");
+        write("README.md", "not a cheat\n");
+        // Links back to a parent are listed once.
+        std::os::unix::fs::symlink(&root, root.join("docker/root")).unwrap();
+
+        root
+    }
+
+    #[tokio::test]
+    async fn test_load_commands() {
+        let root = catalog();
+        let mut matcher = MatcherCommands::new(&SearchConfig { phonetic: Phonetic::Soundex, ..Default::default() }, || {});
+        let loader = CommandMatcher::new(root.clone(), matcher.injector());
+
+        let errors = loader.load_commands().await;
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("broken.cheat:1"));
+
+        while matcher.tick(10).running {}
+        let mut commands = matcher.results().into_iter().map(|hit| hit.command.command.clone()).collect::<Vec<_>>();
+        commands.sort();
+        assert_eq!(commands, vec!["docker compose down", "docker compose up", "git status"]);
+
+        matcher.search(&Query::parse("cmpsdown").unwrap());
+        while matcher.tick(10).running {}
+        let hits = matcher.results().into_iter().map(|hit| (hit.command.command.clone(), hit.kind)).collect::<Vec<_>>();
+        assert_eq!(hits, vec![
+            ("docker compose down".to_string(), MatchKind::Fuzzy),
+            // `cmpsdown` and `compose` share the same Soundex code.
+            ("docker compose up".to_string(), MatchKind::Phonetic),
+        ]);
+
+        matcher.search(&Query::parse("tag:docker -cmd:down").unwrap());
+        while matcher.tick(10).running {}
+        let commands = matcher.results().into_iter().map(|hit| hit.command.command.clone()).collect::<Vec<_>>();
+        assert_eq!(commands, vec!["docker compose up"]);
+
+        matcher.search(&Query::parse("status").unwrap());
+        while matcher.tick(10).running {}
+        let hits = matcher.results();
+        assert_eq!(hits[0].command.command, "git status");
+        // `git status git Status`, the capitalized description wins the tie.
+        assert_eq!(hits[0].indices(), (15..21).collect::<Vec<_>>());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_phonetic_for() {
+        let config = SearchConfig {
+            detect_language: true,
+            catalogs: [
+                (PathBuf::from("/cheats/de"), Phonetic::Cologne),
+                (PathBuf::from("/cheats/de/es"), Phonetic::SpanishSoundex),
+            ]
+            .into(),
+            ..Default::default()
+        };
+        let matcher = MatcherCommands::new(&config, || {});
+
+        let command = |path: Option<&str>, description: &str| Command {
+            command: "ls".into(),
+            tags: vec![],
+            description: description.into(),
+            variables: vec![],
+            source: path.map(|path| Source { path: path.into(), line: 1 }),
+        };
+
+        for (path, description, phonetic) in [
+            (Some("/cheats/de/ls.cheat"), "List files", Phonetic::Cologne),
+            (Some("/cheats/de/es/ls.cheat"), "List files", Phonetic::SpanishSoundex),
+            (Some("/cheats/ls.cheat"), "Alle Dateien anzeigen", Phonetic::Cologne),
+            (None, "Listar todos los archivos", Phonetic::SpanishSoundex),
+            (None, "List all the files", Phonetic::DoubleMetaphone),
+        ] {
+            assert_eq!(matcher.phonetics.phonetic_for(&command(path, description)), phonetic, "{}", description);
+        }
+    }
+
+    #[test]
+    fn test_frecency_order() {
+        let mut matcher = MatcherCommands::new(&SearchConfig::default(), || {});
+        for command in ["git status", "git log", "git push", "ls -la"] {
+            let command = Command {
+                command: command.into(),
+                tags: vec![],
+                description: String::new(),
+                variables: vec![],
+                source: None,
+            };
+            matcher.injector().push(command);
+        }
+
+        let mut history = History::default();
+        history.record("git push", history::now());
+        history.record("ls -la", history::now());
+        history.record("ls -la", history::now());
+        matcher.set_history(history);
+
+        let order = |matcher: &mut MatcherCommands, query: &str| {
+            matcher.search(&Query::parse(query).unwrap());
+            while matcher.tick(10).running {}
+            matcher.results().into_iter().map(|hit| hit.command.command.clone()).collect::<Vec<_>>()
+        };
+
+        // Without a query only the frecency counts, unused commands keep their order.
+        assert_eq!(order(&mut matcher, ""), vec!["ls -la", "git push", "git status", "git log"]);
+        assert_eq!(order(&mut matcher, "git")[0], "git push");
+        assert_eq!(order(&mut matcher, "gitlog"), vec!["git log"]);
+    }
+
+    #[test]
+    fn test_searcher_publishes() {
+        let table = Arc::new(Table::default());
+        let (published, receiver) = mpsc::channel();
+        let searcher = Searcher::spawn(table.clone(), &SearchConfig::default(), move || {
+            let _ = published.send(());
+        });
+
+        for command in ["git status", "git checkout", "ls -la"] {
+            let command = Command {
+                command: command.into(),
+                tags: vec![],
+                description: String::new(),
+                variables: vec![],
+                source: None,
+            };
+            searcher.injector().push(command);
+        }
+
+        searcher.search(Query::parse("git").unwrap());
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while table.load().rows.len() != 2 {
+            assert!(std::time::Instant::now() < deadline, "results were never published");
+            let _ = receiver.recv_timeout(Duration::from_millis(100));
+        }
+
+        let commands = table.load().rows.iter().map(|hit| hit.command.command.clone()).collect::<Vec<_>>();
+        assert!(commands.iter().all(|command| command.starts_with("git")));
+
+        // Settling waits for the query sent before.
+        let (settled, results) = mpsc::channel();
+        searcher.search(Query::parse("ls").unwrap());
+        searcher.settle(move |hits| {
+            let _ = settled.send(hits.iter().map(|hit| hit.command.command.clone()).collect::<Vec<_>>());
+        });
+        assert_eq!(results.recv_timeout(Duration::from_secs(5)).unwrap(), vec!["ls -la"]);
+    }
+}
@@ -0,0 +1,202 @@
+use crate::error::Error;
+use crate::ui::commands::Command;
+
+/// Field of a [`Command`] a filter looks at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Command,
+    Tag,
+    Description,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cmd" | "command" => Some(Field::Command),
+            "tag" | "tags" => Some(Field::Tag),
+            "desc" | "description" => Some(Field::Description),
+            _ => None,
+        }
+    }
+}
+
+/// Condition on a field, `tag:docker` keeps the commands with a tag containing `docker`
+/// and `-tag:docker` the ones without it. Values are compared ignoring case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    pub field: Field,
+    pub value: String,
+    pub negated: bool,
+}
+
+impl Filter {
+    pub fn matches(&self, command: &Command) -> bool {
+        let contains = |text: &str| text.to_lowercase().contains(&self.value);
+        let found = match self.field {
+            Field::Command => contains(&command.command),
+            Field::Tag => command.tags.iter().any(|tag| contains(tag)),
+            Field::Description => contains(&command.description),
+        };
+        found != self.negated
+    }
+}
+
+/// Search typed by the user: filters written as `field:value`, values with spaces between double quotes,
+/// and the rest of the text, which is fuzzy matched.
+///
+/// Words like `http://` whose prefix is not a known field are left in the text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    pub text: String,
+    pub filters: Vec<Filter>,
+}
+
+/// Word of the query, with its quotes removed.
+struct Token {
+    /// Position of its first character, starting at 1.
+    column: usize,
+    text: String,
+}
+
+fn tokens(input: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().enumerate().peekable();
+
+    while let Some(&(start, _)) = chars.peek() {
+        if chars.next_if(|(_, c)| c.is_whitespace()).is_some() {
+            continue;
+        }
+
+        let mut text = String::new();
+        let mut quote = None;
+        while let Some((n, c)) = chars.next_if(|(_, c)| quote.is_some() || !c.is_whitespace()) {
+            match c {
+                '"' if quote.is_some() => quote = None,
+                '"' => quote = Some(n),
+                c => text.push(c),
+            }
+        }
+
+        if let Some(n) = quote {
+            return Err(Error::Query {
+                column: n + 1,
+                message: "unterminated quote".to_string(),
+            });
+        }
+
+        tokens.push(Token { column: start + 1, text });
+    }
+
+    Ok(tokens)
+}
+
+impl Query {
+    /// Splits the input in filters and text, errors point at the column of the token that caused them.
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let mut query = Query::default();
+        let mut words = vec![];
+
+        for token in tokens(input)? {
+            let (negated, filter) = match token.text.strip_prefix('-') {
+                Some(filter) => (true, filter),
+                None => (false, token.text.as_str()),
+            };
+
+            let field = filter
+                .split_once(':')
+                .and_then(|(name, value)| Field::from_name(name).map(|field| (name, field, value)));
+
+            match field {
+                Some((name, _, "")) => {
+                    return Err(Error::Query {
+                        column: token.column,
+                        message: format!("missing value for `{}`", name),
+                    });
+                },
+                Some((_, field, value)) => query.filters.push(Filter {
+                    field,
+                    value: value.to_lowercase(),
+                    negated,
+                }),
+                None => words.push(token.text),
+            }
+        }
+
+        query.text = words.join(" ");
+        Ok(query)
+    }
+
+    /// Whether the command passes every filter, the text is matched elsewhere.
+    pub fn matches(&self, command: &Command) -> bool {
+        self.filters.iter().all(|filter| filter.matches(command))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(field: Field, value: &str, negated: bool) -> Filter {
+        Filter { field, value: value.to_string(), negated }
+    }
+
+    #[test]
+    fn test_parse() {
+        let query = Query::parse(r#"tag:docker -tag:deprecated cmd:compose desc:"Named Volume" up"#).unwrap();
+        assert_eq!(query, Query {
+            text: "up".to_string(),
+            filters: vec![
+                filter(Field::Tag, "docker", false),
+                filter(Field::Tag, "deprecated", true),
+                filter(Field::Command, "compose", false),
+                filter(Field::Description, "named volume", false),
+            ],
+        });
+
+        for (input, text) in [
+            ("", ""),
+            ("  git   log ", "git log"),
+            ("curl http://localhost", "curl http://localhost"),
+            ("-v", "-v"),
+            (r#""git log""#, "git log"),
+        ] {
+            assert_eq!(Query::parse(input).unwrap(), Query { text: text.to_string(), filters: vec![] }, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (input, message) in [
+            ("git tag:", "column 5: missing value for `tag`"),
+            ("-desc:\"\"", "column 1: missing value for `desc`"),
+            ("git desc:\"volume", "column 10: unterminated quote"),
+            ("ñ \"", "column 3: unterminated quote"),
+        ] {
+            assert_eq!(Query::parse(input).unwrap_err().to_string(), message, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let command = Command {
+            command: "docker compose up".into(),
+            tags: vec!["docker".into(), "Compose".into()],
+            description: "Start the services".into(),
+            variables: vec![],
+            source: None,
+        };
+
+        for (input, matches) in [
+            ("tag:docker", true),
+            ("tag:compose", true),
+            ("-tag:deprecated", true),
+            ("-tag:docker", false),
+            ("cmd:compose desc:services", true),
+            ("desc:\"the services\"", true),
+            ("desc:stop", false),
+            ("anything", true),
+        ] {
+            assert_eq!(Query::parse(input).unwrap().matches(&command), matches, "{}", input);
+        }
+    }
+}
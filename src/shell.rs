@@ -0,0 +1,298 @@
+//! Shells the commands are written for, and how values are quoted for each of them.
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+/// Shell that runs the commands, configured with `shell = "fish"` or detected from `$SHELL`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    /// POSIX `sh`, also `dash`, `ash` and `ksh`.
+    #[default]
+    #[serde(alias = "posix")]
+    Sh,
+    #[serde(alias = "pwsh")]
+    PowerShell,
+}
+
+/// Quotes open at some point of a command.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Quoting {
+    #[default]
+    None,
+    Single,
+    Double,
+}
+
+impl Shell {
+    /// Shell of `$SHELL`, POSIX `sh` if it isn't set or isn't known.
+    pub fn detect() -> Self {
+        std::env::var_os("SHELL")
+            .and_then(|shell| Self::from_program(Path::new(&shell)))
+            .unwrap_or_default()
+    }
+
+    /// Shell run by the program, like `/usr/bin/zsh`.
+    pub fn from_program(program: &Path) -> Option<Self> {
+        let name = program.file_stem()?.to_str()?;
+        let shell = match name {
+            "bash" => Shell::Bash,
+            "zsh" => Shell::Zsh,
+            "fish" => Shell::Fish,
+            "sh" | "dash" | "ash" | "ksh" | "mksh" | "busybox" => Shell::Sh,
+            "pwsh" | "powershell" => Shell::PowerShell,
+            _ => return None,
+        };
+        Some(shell)
+    }
+
+    /// Program running the shell.
+    pub fn program(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+            Shell::Sh => "sh",
+            Shell::PowerShell => "pwsh",
+        }
+    }
+
+    /// Option of the program to run the command given after it.
+    pub fn command_flag(self) -> &'static str {
+        match self {
+            Shell::PowerShell => "-Command",
+            _ => "-c",
+        }
+    }
+
+    /// Character escaping the next one outside single quotes.
+    fn escape(self) -> char {
+        match self {
+            Shell::PowerShell => '`',
+            _ => '\\',
+        }
+    }
+
+    /// Quotes open after the text, when it starts with the quotes given open.
+    pub fn scan(self, text: &str, mut quoting: Quoting) -> Quoting {
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            quoting = match (quoting, c) {
+                // Fish is the only one with escapes inside single quotes.
+                (Quoting::Single, '\\') if self == Shell::Fish => {
+                    chars.next();
+                    quoting
+                },
+                (Quoting::None | Quoting::Double, c) if c == self.escape() => {
+                    chars.next();
+                    quoting
+                },
+                (Quoting::None, '\'') => Quoting::Single,
+                (Quoting::None, '"') => Quoting::Double,
+                (Quoting::Single, '\'') | (Quoting::Double, '"') => Quoting::None,
+                _ => quoting,
+            };
+        }
+        quoting
+    }
+
+    /// Value as a single word, quoted if it has anything the shell would expand or split.
+    pub fn quote(self, value: &str) -> String {
+        let safe = |c: char| match self {
+            Shell::PowerShell => c.is_ascii_alphanumeric() || "_./:=+-".contains(c),
+            _ => c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c),
+        };
+        // Zsh expands a leading `=` to the path of the command that follows.
+        let expanded = self == Shell::Zsh && value.starts_with('=');
+        if !value.is_empty() && value.chars().all(safe) && !expanded {
+            return value.to_string();
+        }
+
+        format!("'{}'", self.quote_in(value, Quoting::Single))
+    }
+
+    /// Value written inside the quotes given, so the shell reads it back as it is.
+    pub fn quote_in(self, value: &str, quoting: Quoting) -> String {
+        let mut quoted = String::with_capacity(value.len());
+        match (self, quoting) {
+            (_, Quoting::None) => return self.quote(value),
+            (Shell::Bash | Shell::Zsh | Shell::Sh, Quoting::Single) => {
+                for c in value.chars() {
+                    match c {
+                        '\'' => quoted.push_str("'\\''"),
+                        c => quoted.push(c),
+                    }
+                }
+            },
+            (Shell::Bash | Shell::Zsh | Shell::Sh, Quoting::Double) => {
+                for c in value.chars() {
+                    match c {
+                        '\\' | '"' | '$' | '`' => {
+                            quoted.push('\\');
+                            quoted.push(c);
+                        },
+                        // History expansion happens even in double quotes, but never in single ones.
+                        '!' if self != Shell::Sh => quoted.push_str("\"'!'\""),
+                        c => quoted.push(c),
+                    }
+                }
+            },
+            (Shell::Fish, Quoting::Single) => {
+                for c in value.chars() {
+                    if matches!(c, '\\' | '\'') {
+                        quoted.push('\\');
+                    }
+                    quoted.push(c);
+                }
+            },
+            (Shell::Fish, Quoting::Double) => {
+                for c in value.chars() {
+                    if matches!(c, '\\' | '"' | '$') {
+                        quoted.push('\\');
+                    }
+                    quoted.push(c);
+                }
+            },
+            // PowerShell takes the typographic quotes as quotes too.
+            (Shell::PowerShell, Quoting::Single) => {
+                for c in value.chars() {
+                    if matches!(c, '\'' | '‘' | '’' | '‚' | '‛') {
+                        quoted.push(c);
+                    }
+                    quoted.push(c);
+                }
+            },
+            (Shell::PowerShell, Quoting::Double) => {
+                for c in value.chars() {
+                    if matches!(c, '`' | '"' | '$' | '“' | '”' | '„') {
+                        quoted.push('`');
+                    }
+                    quoted.push(c);
+                }
+            },
+        }
+        quoted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALUES: [&str; 16] = [
+        "plain",
+        "two words",
+        "it's",
+        "say \"hi\"",
+        "$HOME ${PATH}",
+        "`id` $(id)",
+        "back\\slash\\",
+        "two\nlines",
+        "!event !!",
+        "*.rs ?",
+        "~/src",
+        "",
+        "tab\there; a && b | c > d",
+        "ünïcode ‘quoted’",
+        "{a,b} #comment",
+        "=ls",
+    ];
+
+    /// Arguments the shell gives to `printf` for the command, `None` if the shell isn't installed.
+    fn argv(program: &str, shell: Shell, arguments: &str) -> Option<Vec<String>> {
+        let output = std::process::Command::new(program)
+            .arg(shell.command_flag())
+            .arg(format!("printf '%s\\0' {}", arguments))
+            .output()
+            .ok()?;
+        assert!(output.status.success(), "{}: {}", program, String::from_utf8_lossy(&output.stderr));
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        Some(stdout.split_terminator('\0').map(String::from).collect())
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for (program, shell) in [
+            ("bash", Shell::Bash),
+            ("sh", Shell::Sh),
+            ("dash", Shell::Sh),
+            ("zsh", Shell::Zsh),
+            ("fish", Shell::Fish),
+            ("pwsh", Shell::PowerShell),
+        ] {
+            for value in VALUES {
+                let arguments = format!(
+                    "{} \"<{}>\" '<{}>'",
+                    shell.quote(value),
+                    shell.quote_in(value, Quoting::Double),
+                    shell.quote_in(value, Quoting::Single),
+                );
+                let Some(argv) = argv(program, shell, &arguments) else {
+                    eprintln!("{} is not installed, its quoting is not round-tripped", program);
+                    break;
+                };
+                let inside = format!("<{}>", value);
+                assert_eq!(argv, [value, &inside, &inside], "{}: {}", program, arguments);
+            }
+        }
+    }
+
+    #[test]
+    fn test_quote() {
+        for (shell, value, quoted) in [
+            (Shell::Bash, "main", "main"),
+            (Shell::Bash, "it's", "'it'\\''s'"),
+            (Shell::Sh, "", "''"),
+            (Shell::Zsh, "=ls", "'=ls'"),
+            (Shell::Zsh, "a=b", "a=b"),
+            (Shell::Bash, "=ls", "=ls"),
+            (Shell::Fish, "it's \\o/", "'it\\'s \\\\o/'"),
+            (Shell::PowerShell, "it's $x", "'it''s $x'"),
+            (Shell::PowerShell, "a,b", "'a,b'"),
+        ] {
+            assert_eq!(shell.quote(value), quoted, "{:?} {}", shell, value);
+        }
+
+        for (shell, value, quoted) in [
+            (Shell::Bash, "say \"$x\"!", "say \\\"\\$x\\\"\"'!'\""),
+            (Shell::Sh, "wow!", "wow!"),
+            (Shell::Fish, "\"$x\"", "\\\"\\$x\\\""),
+            (Shell::PowerShell, "\"$x\"", "`\"`$x`\""),
+        ] {
+            assert_eq!(shell.quote_in(value, Quoting::Double), quoted, "{:?} {}", shell, value);
+        }
+    }
+
+    #[test]
+    fn test_scan() {
+        for (shell, text, quoting) in [
+            (Shell::Bash, "git commit -m \"", Quoting::Double),
+            (Shell::Bash, "echo 'a\" ", Quoting::Single),
+            (Shell::Bash, "echo \\\"", Quoting::None),
+            (Shell::Bash, "echo \"a\\\" ", Quoting::Double),
+            (Shell::Bash, "echo 'a\\' ", Quoting::None),
+            (Shell::Fish, "echo 'a\\' ", Quoting::Single),
+            (Shell::PowerShell, "echo \"a`\" ", Quoting::Double),
+        ] {
+            assert_eq!(shell.scan(text, Quoting::None), quoting, "{:?} {}", shell, text);
+        }
+    }
+
+    #[test]
+    fn test_detect() {
+        for (program, shell) in [
+            ("/bin/bash", Some(Shell::Bash)),
+            ("/usr/local/bin/fish", Some(Shell::Fish)),
+            ("/bin/dash", Some(Shell::Sh)),
+            ("pwsh.exe", Some(Shell::PowerShell)),
+            ("/usr/bin/nu", None),
+        ] {
+            assert_eq!(Shell::from_program(Path::new(program)), shell, "{}", program);
+        }
+    }
+}
@@ -0,0 +1,1203 @@
+//! Syntax tree of the commands, as the POSIX shell reads them: lists of pipelines of simple and
+//! compound commands, made of words, redirections and substitutions.
+//!
+//! Placeholders, `<name>` and `{{…}}`, are opaque parts of the words they appear in. The parser never
+//! fails: what it can't read is reported as a [`Diagnostic`] and skipped up to the next command.
+
+use std::ops::Range;
+
+use crate::cheat::is_variable_char;
+
+/// Commands nested deeper than this, like `$($($(…)))`, are reported instead of parsed.
+const MAX_DEPTH: usize = 64;
+
+/// Words with a meaning at the start of a command.
+const RESERVED: [&str; 16] =
+    ["if", "then", "else", "elif", "fi", "do", "done", "case", "esac", "while", "until", "for", "in", "{", "}", "!"];
+
+/// Commands run one after the other, separated by `;`, `&` or line breaks.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct List {
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub and_or: AndOr,
+    /// Ended with `&`.
+    pub background: bool,
+}
+
+/// Pipelines run depending on the status of the previous one, `a && b || c`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AndOr {
+    pub first: Pipeline,
+    pub rest: Vec<(Connector, Pipeline)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connector {
+    And,
+    Or,
+}
+
+/// Commands connected by `|`, the status negated with `!`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    pub negated: bool,
+    pub commands: Vec<Command>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Simple(SimpleCommand),
+    Compound { compound: Compound, redirections: Vec<Redirection> },
+    /// `name() body`
+    Function { name: String, body: Box<Command> },
+}
+
+/// Program with its arguments, after the variables set for it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SimpleCommand {
+    pub assignments: Vec<Assignment>,
+    pub words: Vec<Word>,
+    pub redirections: Vec<Redirection>,
+    /// Byte range of the command in the source.
+    pub span: Range<usize>,
+}
+
+/// `name=value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    pub name: String,
+    pub value: Word,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compound {
+    /// `{ list; }`
+    Group(List),
+    /// `( list )`
+    Subshell(List),
+    /// Conditions with their lists, the `if` and then each `elif`, and the `else` list.
+    If { branches: Vec<(List, List)>, otherwise: Option<List> },
+    /// `while` or `until` loop.
+    While { until: bool, condition: List, body: List },
+    /// `for name in words; do body; done`, without words for the arguments.
+    For { variable: String, words: Option<Vec<Word>>, body: List },
+    Case { word: Word, arms: Vec<CaseArm> },
+}
+
+/// `pattern | pattern) list ;;`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseArm {
+    pub patterns: Vec<Word>,
+    pub body: List,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Redirect {
+    /// `<`
+    Input,
+    /// `>`
+    Output,
+    /// `>>`
+    Append,
+    /// `>|`
+    Clobber,
+    /// `<>`
+    ReadWrite,
+    /// `<&`
+    DuplicateInput,
+    /// `>&`
+    DuplicateOutput,
+    /// `<<`, or `<<-` stripping the leading tabs.
+    HereDocument { strip_tabs: bool },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirection {
+    /// File descriptor written before the operator, like the 2 of `2>&1`.
+    pub fd: Option<u32>,
+    pub redirect: Redirect,
+    /// File, descriptor or delimiter of the here-document.
+    pub target: Word,
+    /// Lines of the here-document.
+    pub here_document: Option<String>,
+}
+
+/// Word, made of the parts written next to each other.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Word {
+    pub parts: Vec<WordPart>,
+    /// Byte range of the word in the source.
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordPart {
+    /// Unquoted text, with its escapes removed.
+    Literal(String),
+    /// `'text'`
+    SingleQuoted(String),
+    /// `"…"`, with the parts expanded inside.
+    DoubleQuoted(Vec<WordPart>),
+    /// `$name` or `${name…}`.
+    Parameter(Parameter),
+    /// `$(list)` or `` `list` ``.
+    CommandSubstitution(List),
+    /// `$((expression))`
+    Arithmetic(String),
+    /// `<name>`, or the expression of `{{…}}`.
+    Placeholder(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parameter {
+    /// Name, position or special parameter, like `@` or `?`.
+    pub name: String,
+    /// What goes with the name inside the braces, like `:-default` after it or `#` before it for the length.
+    pub modifier: String,
+}
+
+/// Problem found in the command, the parser goes on after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Byte range of the problem in the source.
+    pub range: Range<usize>,
+    pub message: String,
+}
+
+/// Tree of a command, with the problems found reading it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Parsed {
+    pub list: List,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Word {
+    /// Text of the word if nothing in it is expanded.
+    pub fn literal(&self) -> Option<String> {
+        self.parts.iter().try_fold(String::new(), |mut text, part| {
+            match part {
+                WordPart::Literal(literal) | WordPart::SingleQuoted(literal) => text.push_str(literal),
+                WordPart::DoubleQuoted(parts) => text.push_str(&Word { parts: parts.clone(), span: 0..0 }.literal()?),
+                _ => return None,
+            }
+            Some(text)
+        })
+    }
+
+    fn substitutions<'a>(parts: &'a [WordPart], commands: &mut Vec<&'a SimpleCommand>) {
+        for part in parts {
+            match part {
+                WordPart::CommandSubstitution(list) => list.collect(commands),
+                WordPart::DoubleQuoted(parts) => Self::substitutions(parts, commands),
+                _ => {},
+            }
+        }
+    }
+}
+
+impl SimpleCommand {
+    /// Name of the program, if it is written as is.
+    pub fn name(&self) -> Option<String> {
+        self.words.first().and_then(Word::literal)
+    }
+}
+
+impl List {
+    /// Every simple command, the ones in compound commands and substitutions too, in the order they are written.
+    pub fn simple_commands(&self) -> Vec<&SimpleCommand> {
+        let mut commands = vec![];
+        self.collect(&mut commands);
+        commands
+    }
+
+    fn collect<'a>(&'a self, commands: &mut Vec<&'a SimpleCommand>) {
+        let pipelines = self.items.iter().flat_map(|item| {
+            std::iter::once(&item.and_or.first).chain(item.and_or.rest.iter().map(|(_, pipeline)| pipeline))
+        });
+        for command in pipelines.flat_map(|pipeline| &pipeline.commands) {
+            command.collect(commands);
+        }
+    }
+}
+
+impl Command {
+    fn collect<'a>(&'a self, commands: &mut Vec<&'a SimpleCommand>) {
+        match self {
+            Command::Simple(simple) => {
+                commands.push(simple);
+                let words = simple.assignments.iter().map(|assignment| &assignment.value);
+                let words = words.chain(&simple.words).chain(simple.redirections.iter().map(|redirection| &redirection.target));
+                for word in words {
+                    Word::substitutions(&word.parts, commands);
+                }
+            },
+            Command::Compound { compound, .. } => match compound {
+                Compound::Group(list) | Compound::Subshell(list) => list.collect(commands),
+                Compound::If { branches, otherwise } => {
+                    for (condition, body) in branches {
+                        condition.collect(commands);
+                        body.collect(commands);
+                    }
+                    if let Some(otherwise) = otherwise {
+                        otherwise.collect(commands);
+                    }
+                },
+                Compound::While { condition, body, .. } => {
+                    condition.collect(commands);
+                    body.collect(commands);
+                },
+                Compound::For { words, body, .. } => {
+                    for word in words.iter().flatten() {
+                        Word::substitutions(&word.parts, commands);
+                    }
+                    body.collect(commands);
+                },
+                Compound::Case { word, arms } => {
+                    Word::substitutions(&word.parts, commands);
+                    for arm in arms {
+                        arm.body.collect(commands);
+                    }
+                },
+            },
+            Command::Function { body, .. } => body.collect(commands),
+        }
+    }
+}
+
+/// Characters ending an unquoted word.
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || ";&|()<>".contains(c)
+}
+
+fn is_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Adds the text to the last part if it is a literal too.
+fn push_literal(parts: &mut Vec<WordPart>, text: &str) {
+    match parts.last_mut() {
+        Some(WordPart::Literal(literal)) => literal.push_str(text),
+        _ => parts.push(WordPart::Literal(text.to_string())),
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+    diagnostics: Vec<Diagnostic>,
+    depth: usize,
+    /// End of the here-documents started on the current line, skipped at its line break.
+    here_documents_end: Option<usize>,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&mut self, range: Range<usize>, message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic { range, message: message.into() });
+    }
+
+    /// Skips spaces, escaped line breaks and comments, but not line breaks.
+    fn blanks(&mut self) {
+        loop {
+            let rest = self.rest();
+            if rest.starts_with([' ', '\t']) {
+                self.position += 1;
+            } else if rest.starts_with("\\\n") {
+                self.position += 2;
+            } else if rest.starts_with('#') {
+                self.position += rest.find('\n').unwrap_or(rest.len());
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Skips blanks and line breaks, and the here-documents after them.
+    fn linebreaks(&mut self) {
+        loop {
+            self.blanks();
+            if self.peek() != Some('\n') {
+                break;
+            }
+            self.position += 1;
+            if let Some(end) = self.here_documents_end.take() {
+                self.position = self.position.max(end);
+            }
+        }
+    }
+
+    /// Reserved word at the position, if it is a whole word.
+    fn reserved(&self) -> Option<&'static str> {
+        let rest = self.rest();
+        RESERVED
+            .into_iter()
+            .find(|word| rest.starts_with(word) && rest[word.len()..].chars().next().is_none_or(is_delimiter))
+    }
+
+    /// Length of the `<name>` placeholder at the position, if there is one.
+    fn placeholder(&self) -> Option<usize> {
+        let name = self.rest().strip_prefix('<')?;
+        let length = name.find(|c: char| !is_variable_char(c)).unwrap_or(name.len());
+        (length > 0 && name[length..].starts_with('>')).then_some(length + 2)
+    }
+
+    /// Whether the list ends at the position, with one of the terminators or the end of the source.
+    fn at_terminator(&self, terminators: &[&str]) -> bool {
+        let rest = self.rest();
+        rest.is_empty()
+            || (terminators.contains(&")") && rest.starts_with(')'))
+            || (terminators.contains(&";;") && rest.starts_with(";;"))
+            || self.reserved().is_some_and(|word| terminators.contains(&word))
+    }
+
+    /// Consumes the reserved word closing what was opened at `start`.
+    fn expect(&mut self, word: &str, start: usize, opened: &str) {
+        self.linebreaks();
+        if self.reserved() == Some(word) {
+            self.position += word.len();
+        } else {
+            self.error(start..start + opened.len(), format!("`{}` isn't closed, expected `{}`", opened, word));
+        }
+    }
+
+    fn list(&mut self, terminators: &[&str]) -> List {
+        let mut list = List::default();
+        if self.depth >= MAX_DEPTH {
+            self.error(self.position..self.source.len(), "commands are nested too deeply");
+            self.position = self.source.len();
+            return list;
+        }
+
+        self.depth += 1;
+        loop {
+            self.linebreaks();
+            if self.at_terminator(terminators) {
+                break;
+            }
+
+            let start = self.position;
+            let and_or = self.and_or();
+            self.blanks();
+            let background = match self.peek() {
+                Some('&') => {
+                    self.position += 1;
+                    true
+                },
+                Some(';') if !self.rest().starts_with(";;") => {
+                    self.position += 1;
+                    false
+                },
+                Some('\n') | None => false,
+                _ if self.at_terminator(terminators) => false,
+                Some(c) => {
+                    let end = self.position + c.len_utf8();
+                    self.error(self.position..end, format!("unexpected `{}`", c));
+                    self.recover();
+                    false
+                },
+            };
+            list.items.push(Item { and_or, background });
+
+            // Anything that can't start a command is skipped by the errors, this is only a safeguard.
+            if self.position == start {
+                self.bump();
+            }
+        }
+        self.depth -= 1;
+        list
+    }
+
+    /// Skips to the next command, after a `;` or `&`, or at a line break.
+    fn recover(&mut self) {
+        while let Some(c) = self.peek() {
+            match c {
+                '\n' => break,
+                ';' | '&' => {
+                    self.position += 1;
+                    break;
+                },
+                _ => self.position += c.len_utf8(),
+            }
+        }
+    }
+
+    fn and_or(&mut self) -> AndOr {
+        let first = self.pipeline();
+        let mut rest = vec![];
+        loop {
+            self.blanks();
+            let connector = match self.rest() {
+                rest if rest.starts_with("&&") => Connector::And,
+                rest if rest.starts_with("||") => Connector::Or,
+                _ => break,
+            };
+            self.position += 2;
+            self.linebreaks();
+            rest.push((connector, self.pipeline()));
+        }
+        AndOr { first, rest }
+    }
+
+    fn pipeline(&mut self) -> Pipeline {
+        self.blanks();
+        let negated = self.reserved() == Some("!");
+        if negated {
+            self.position += 1;
+        }
+
+        let mut commands = vec![self.command()];
+        loop {
+            self.blanks();
+            let rest = self.rest();
+            if !rest.starts_with('|') || rest.starts_with("||") {
+                break;
+            }
+            self.position += 1;
+            self.linebreaks();
+            commands.push(self.command());
+        }
+        Pipeline { negated, commands }
+    }
+
+    fn command(&mut self) -> Command {
+        self.blanks();
+        let start = self.position;
+        let compound = match self.reserved() {
+            Some("{") => {
+                self.position += 1;
+                let list = self.list(&["}"]);
+                self.expect("}", start, "{");
+                Compound::Group(list)
+            },
+            Some("if") => self.if_clause(),
+            Some(word @ ("while" | "until")) => {
+                self.position += word.len();
+                let condition = self.list(&["do"]);
+                self.expect("do", start, word);
+                let body = self.list(&["done"]);
+                self.expect("done", start, word);
+                Compound::While { until: word == "until", condition, body }
+            },
+            Some("for") => self.for_clause(),
+            Some("case") => self.case_clause(),
+            Some(word) if word != "!" => {
+                self.error(start..start + word.len(), format!("unexpected `{}`", word));
+                self.position += word.len();
+                return Command::Simple(SimpleCommand { span: start..self.position, ..Default::default() });
+            },
+            _ if self.peek() == Some('(') => {
+                self.position += 1;
+                let list = self.list(&[")"]);
+                self.linebreaks();
+                if self.peek() == Some(')') {
+                    self.position += 1;
+                } else {
+                    self.error(start..start + 1, "`(` isn't closed, expected `)`");
+                }
+                Compound::Subshell(list)
+            },
+            _ => return self.simple_command(),
+        };
+
+        let mut redirections = vec![];
+        loop {
+            self.blanks();
+            match self.redirection() {
+                Some(redirection) => redirections.push(redirection),
+                None => break,
+            }
+        }
+        Command::Compound { compound, redirections }
+    }
+
+    fn if_clause(&mut self) -> Compound {
+        let start = self.position;
+        self.position += "if".len();
+        let mut branches = vec![];
+        let mut otherwise = None;
+
+        loop {
+            let condition = self.list(&["then"]);
+            self.expect("then", start, "if");
+            let body = self.list(&["elif", "else", "fi"]);
+            branches.push((condition, body));
+
+            self.linebreaks();
+            match self.reserved() {
+                Some("elif") => self.position += "elif".len(),
+                Some("else") => {
+                    self.position += "else".len();
+                    otherwise = Some(self.list(&["fi"]));
+                    self.expect("fi", start, "if");
+                    break;
+                },
+                _ => {
+                    self.expect("fi", start, "if");
+                    break;
+                },
+            }
+        }
+        Compound::If { branches, otherwise }
+    }
+
+    fn for_clause(&mut self) -> Compound {
+        let start = self.position;
+        self.position += "for".len();
+        self.blanks();
+
+        let name_length = self.rest().find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(self.rest().len());
+        let variable = self.rest()[..name_length].to_string();
+        if !is_name(&variable) {
+            self.error(self.position..self.position + name_length, "expected the name of the variable after `for`");
+        }
+        self.position += name_length;
+
+        self.linebreaks();
+        let mut words = None;
+        if self.reserved() == Some("in") {
+            self.position += "in".len();
+            let mut list = vec![];
+            loop {
+                self.blanks();
+                match self.peek() {
+                    None | Some(';' | '\n') => break,
+                    Some(c) if is_delimiter(c) && self.placeholder().is_none() => {
+                        self.error(self.position..self.position + 1, format!("unexpected `{}`", c));
+                        self.position += 1;
+                    },
+                    _ => list.push(self.word()),
+                }
+            }
+            words = Some(list);
+        }
+        self.blanks();
+        if self.peek() == Some(';') {
+            self.position += 1;
+        }
+
+        self.expect("do", start, "for");
+        let body = self.list(&["done"]);
+        self.expect("done", start, "for");
+        Compound::For { variable, words, body }
+    }
+
+    fn case_clause(&mut self) -> Compound {
+        let start = self.position;
+        self.position += "case".len();
+        self.blanks();
+        let word = self.word();
+        if word.parts.is_empty() {
+            self.error(start..start + 4, "expected a word after `case`");
+        }
+        self.expect("in", start, "case");
+
+        let mut arms = vec![];
+        loop {
+            self.linebreaks();
+            if self.reserved() == Some("esac") {
+                self.position += "esac".len();
+                break;
+            }
+            if self.rest().is_empty() {
+                self.error(start..start + 4, "`case` isn't closed, expected `esac`");
+                break;
+            }
+
+            if self.peek() == Some('(') {
+                self.position += 1;
+            }
+            let mut patterns = vec![];
+            loop {
+                self.blanks();
+                let pattern = self.word();
+                if pattern.parts.is_empty() {
+                    break;
+                }
+                patterns.push(pattern);
+                self.blanks();
+                if self.peek() != Some('|') {
+                    break;
+                }
+                self.position += 1;
+            }
+
+            self.blanks();
+            if patterns.is_empty() || self.peek() != Some(')') {
+                self.error(self.position..self.position + 1, "expected a pattern and `)`");
+                self.recover();
+                continue;
+            }
+            self.position += 1;
+
+            let body = self.list(&[";;", "esac"]);
+            arms.push(CaseArm { patterns, body });
+            self.linebreaks();
+            if self.rest().starts_with(";;") {
+                self.position += 2;
+            }
+        }
+        Compound::Case { word, arms }
+    }
+
+    fn simple_command(&mut self) -> Command {
+        let start = self.position;
+        let mut command = SimpleCommand::default();
+        let mut end = start;
+
+        loop {
+            self.blanks();
+            match self.peek() {
+                None | Some(';' | '&' | '|' | '(' | ')' | '\n') => break,
+                _ => {},
+            }
+
+            if let Some(redirection) = self.redirection() {
+                command.redirections.push(redirection);
+            } else {
+                let word = self.word();
+                if word.parts.is_empty() && word.span.is_empty() {
+                    break;
+                }
+                match assignment(&word).filter(|_| command.words.is_empty()) {
+                    Some(assignment) => command.assignments.push(assignment),
+                    None => command.words.push(word),
+                }
+            }
+            end = self.position;
+        }
+        command.span = start..end;
+
+        // `name() body` defines a function.
+        let function = command.words.len() == 1 && command.assignments.is_empty() && command.redirections.is_empty();
+        if let Some(name) = command.name().filter(|name| function && is_name(name) && self.peek() == Some('(')) {
+            self.position += 1;
+            self.blanks();
+            if self.peek() == Some(')') {
+                self.position += 1;
+            } else {
+                self.error(self.position..self.position + 1, "expected `)` after `(` in the function definition");
+            }
+            self.linebreaks();
+            let body = Box::new(self.command());
+            return Command::Function { name, body };
+        }
+
+        if command == (SimpleCommand { span: start..start, ..Default::default() }) {
+            let found = self.peek().map_or("the end".to_string(), |c| format!("`{}`", c.escape_debug()));
+            self.error(start..start + self.peek().map_or(0, char::len_utf8), format!("expected a command, found {}", found));
+        }
+        Command::Simple(command)
+    }
+
+    fn redirection(&mut self) -> Option<Redirection> {
+        let start = self.position;
+        let rest = self.rest();
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if digits == 0 && self.placeholder().is_some() {
+            return None;
+        }
+
+        let (redirect, length) = match &rest[digits..] {
+            after if after.starts_with("<<-") => (Redirect::HereDocument { strip_tabs: true }, 3),
+            after if after.starts_with("<<") => (Redirect::HereDocument { strip_tabs: false }, 2),
+            after if after.starts_with("<&") => (Redirect::DuplicateInput, 2),
+            after if after.starts_with("<>") => (Redirect::ReadWrite, 2),
+            after if after.starts_with('<') => (Redirect::Input, 1),
+            after if after.starts_with(">>") => (Redirect::Append, 2),
+            after if after.starts_with(">&") => (Redirect::DuplicateOutput, 2),
+            after if after.starts_with(">|") => (Redirect::Clobber, 2),
+            after if after.starts_with('>') => (Redirect::Output, 1),
+            _ => return None,
+        };
+        let fd = match digits {
+            0 => None,
+            _ => rest[..digits].parse().ok(),
+        };
+        if digits > 0 && fd.is_none() {
+            self.error(start..start + digits, "file descriptor out of range");
+        }
+        self.position += digits + length;
+        let operator = start + digits..self.position;
+
+        self.blanks();
+        let target = self.word();
+        if target.parts.is_empty() {
+            let message = format!("expected a word after `{}`", &self.source[operator.clone()]);
+            self.error(operator, message);
+        }
+
+        let here_document = match redirect {
+            Redirect::HereDocument { strip_tabs } => self.here_document(&target, strip_tabs),
+            _ => None,
+        };
+        Some(Redirection { fd, redirect, target, here_document })
+    }
+
+    /// Reads the lines of the here-document, they start at the next line break.
+    fn here_document(&mut self, delimiter: &Word, strip_tabs: bool) -> Option<String> {
+        let delimiter = delimiter.literal().unwrap_or_default();
+        let start = match self.here_documents_end {
+            Some(end) => end,
+            None => self.position + self.rest().find('\n')? + 1,
+        };
+
+        let mut body = String::new();
+        let mut end = start;
+        for line in self.source[start..].split_inclusive('\n') {
+            end += line.len();
+            let content = line.strip_suffix('\n').unwrap_or(line);
+            let content = if strip_tabs { content.trim_start_matches('\t') } else { content };
+            if content == delimiter {
+                self.here_documents_end = Some(end);
+                return Some(body);
+            }
+            body.push_str(content);
+            body.push('\n');
+        }
+
+        let operator = self.position.saturating_sub(delimiter.len())..self.position;
+        self.error(operator, format!("here-document isn't closed by `{}`", delimiter));
+        self.here_documents_end = Some(self.source.len());
+        Some(body)
+    }
+
+    fn word(&mut self) -> Word {
+        let start = self.position;
+        let mut parts = vec![];
+        while let Some(c) = self.peek() {
+            match c {
+                '<' if self.placeholder().is_some() => self.placeholder_part(&mut parts),
+                c if is_delimiter(c) => break,
+                '\\' => {
+                    self.position += 1;
+                    match self.bump() {
+                        Some('\n') => {},
+                        Some(escaped) => push_literal(&mut parts, escaped.encode_utf8(&mut [0; 4])),
+                        None => push_literal(&mut parts, "\\"),
+                    }
+                },
+                '\'' => {
+                    self.position += 1;
+                    let length = self.rest().find('\'').unwrap_or_else(|| {
+                        self.error(self.position - 1..self.position, "`'` isn't closed");
+                        self.rest().len()
+                    });
+                    parts.push(WordPart::SingleQuoted(self.rest()[..length].to_string()));
+                    self.position = (self.position + length + 1).min(self.source.len());
+                },
+                '"' => {
+                    let inside = self.double_quoted();
+                    parts.push(WordPart::DoubleQuoted(inside));
+                },
+                '$' => self.dollar(&mut parts),
+                '`' => self.backquoted(&mut parts),
+                '{' if self.rest().starts_with("{{") => self.placeholder_part(&mut parts),
+                c => {
+                    self.position += c.len_utf8();
+                    push_literal(&mut parts, c.encode_utf8(&mut [0; 4]));
+                },
+            }
+        }
+        Word { parts, span: start..self.position }
+    }
+
+    /// Parts of the double-quoted text at the position, the quotes included.
+    fn double_quoted(&mut self) -> Vec<WordPart> {
+        let start = self.position;
+        self.position += 1;
+        let mut parts = vec![];
+        loop {
+            match self.peek() {
+                None => {
+                    self.error(start..start + 1, "`\"` isn't closed");
+                    break;
+                },
+                Some('"') => {
+                    self.position += 1;
+                    break;
+                },
+                Some('\\') => {
+                    self.position += 1;
+                    match self.bump() {
+                        Some('\n') => {},
+                        Some(c @ ('$' | '`' | '"' | '\\')) => push_literal(&mut parts, c.encode_utf8(&mut [0; 4])),
+                        Some(c) => {
+                            push_literal(&mut parts, "\\");
+                            push_literal(&mut parts, c.encode_utf8(&mut [0; 4]));
+                        },
+                        None => push_literal(&mut parts, "\\"),
+                    }
+                },
+                Some('$') => self.dollar(&mut parts),
+                Some('`') => self.backquoted(&mut parts),
+                Some('<') if self.placeholder().is_some() => self.placeholder_part(&mut parts),
+                Some('{') if self.rest().starts_with("{{") => self.placeholder_part(&mut parts),
+                Some(c) => {
+                    self.position += c.len_utf8();
+                    push_literal(&mut parts, c.encode_utf8(&mut [0; 4]));
+                },
+            }
+        }
+        parts
+    }
+
+    /// `<name>` or `{{expression}}` at the position.
+    fn placeholder_part(&mut self, parts: &mut Vec<WordPart>) {
+        if let Some(length) = self.placeholder() {
+            let name = &self.rest()[1..length - 1];
+            parts.push(WordPart::Placeholder(name.to_string()));
+            self.position += length;
+            return;
+        }
+
+        let start = self.position;
+        let inside = &self.rest()[2..];
+        let length = inside.find("}}").unwrap_or_else(|| {
+            self.error(start..start + 2, "`{{` isn't closed");
+            inside.len()
+        });
+        parts.push(WordPart::Placeholder(inside[..length].trim().to_string()));
+        self.position = (start + 2 + length + 2).min(self.source.len());
+    }
+
+    /// Expansion starting with `$` at the position, or the `$` itself if nothing follows it.
+    fn dollar(&mut self, parts: &mut Vec<WordPart>) {
+        let start = self.position;
+        let rest = &self.rest()[1..];
+
+        if let Some(inside) = rest.strip_prefix("((") {
+            if let Some(length) = arithmetic_length(inside) {
+                parts.push(WordPart::Arithmetic(inside[..length].to_string()));
+                self.position = start + 3 + length + 2;
+                return;
+            }
+        }
+
+        if rest.starts_with('(') {
+            self.position += 2;
+            let list = self.list(&[")"]);
+            self.linebreaks();
+            if self.peek() == Some(')') {
+                self.position += 1;
+            } else {
+                self.error(start..start + 2, "`$(` isn't closed, expected `)`");
+            }
+            parts.push(WordPart::CommandSubstitution(list));
+            return;
+        }
+
+        if let Some(inside) = rest.strip_prefix('{') {
+            let length = braces_length(inside).unwrap_or_else(|| {
+                self.error(start..start + 2, "`${` isn't closed, expected `}`");
+                inside.len()
+            });
+            self.position = (start + 2 + length + 1).min(self.source.len());
+            match parameter(&inside[..length]) {
+                Some(parameter) => parts.push(WordPart::Parameter(parameter)),
+                None => self.error(start..self.position, "bad substitution"),
+            }
+            return;
+        }
+
+        let length = match rest.chars().next() {
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len())
+            },
+            Some(c) if c.is_ascii_digit() || "@*#?-$!".contains(c) => 1,
+            _ => 0,
+        };
+        self.position += 1 + length;
+        match length {
+            0 => push_literal(parts, "$"),
+            _ => parts.push(WordPart::Parameter(Parameter { name: rest[..length].to_string(), modifier: String::new() })),
+        }
+    }
+
+    /// `` `list` `` at the position, its commands are read in place, up to the closing backquote.
+    fn backquoted(&mut self, parts: &mut Vec<WordPart>) {
+        let start = self.position;
+        let mut end = None;
+        let mut chars = self.rest().char_indices().skip(1);
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                },
+                '`' => {
+                    end = Some(start + index);
+                    break;
+                },
+                _ => {},
+            }
+        }
+        let end = end.unwrap_or_else(|| {
+            self.error(start..start + 1, "`` ` `` isn't closed");
+            self.source.len()
+        });
+
+        let mut inner = Parser {
+            source: &self.source[..end],
+            position: start + 1,
+            diagnostics: vec![],
+            depth: self.depth + 1,
+            here_documents_end: None,
+        };
+        let list = inner.list(&[]);
+        self.diagnostics.append(&mut inner.diagnostics);
+        parts.push(WordPart::CommandSubstitution(list));
+        self.position = (end + 1).min(self.source.len());
+    }
+}
+
+/// Assignment the word makes, if it starts with `name=`.
+fn assignment(word: &Word) -> Option<Assignment> {
+    let Some(WordPart::Literal(literal)) = word.parts.first() else {
+        return None;
+    };
+    let (name, value) = literal.split_once('=')?;
+    if !is_name(name) {
+        return None;
+    }
+
+    let mut parts = word.parts.clone();
+    match value {
+        "" => {
+            parts.remove(0);
+        },
+        value => parts[0] = WordPart::Literal(value.to_string()),
+    }
+    let span = word.span.start + name.len() + 1..word.span.end;
+    Some(Assignment { name: name.to_string(), value: Word { parts, span } })
+}
+
+/// Length of the expression of `$((…))` up to its `))`, if the parentheses match.
+fn arithmetic_length(inside: &str) -> Option<usize> {
+    let mut depth = 0_usize;
+    for (index, c) in inside.char_indices() {
+        match c {
+            ')' if depth == 0 => return inside[index..].starts_with("))").then_some(index),
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {},
+        }
+    }
+    None
+}
+
+/// Length of the text of `${…}` up to its closing brace, skipping nested braces and quotes.
+fn braces_length(inside: &str) -> Option<usize> {
+    let mut depth = 0_usize;
+    let mut chars = inside.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            },
+            '\'' => {
+                chars.find(|(_, c)| *c == '\'');
+            },
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(index),
+            '}' => depth -= 1,
+            _ => {},
+        }
+    }
+    None
+}
+
+/// Parameter of the text inside `${…}`.
+fn parameter(inside: &str) -> Option<Parameter> {
+    // `${#name}` is the length of the parameter, `${#}` the number of arguments.
+    let (prefix, rest) = match inside.strip_prefix('#') {
+        Some(rest) if !rest.is_empty() => ("#", rest),
+        _ => ("", inside),
+    };
+
+    let length = match rest.chars().next()? {
+        c if c.is_ascii_alphabetic() || c == '_' => {
+            rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len())
+        },
+        c if c.is_ascii_digit() => rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len()),
+        c if "@*#?-$!".contains(c) => 1,
+        _ => return None,
+    };
+    let modifier = format!("{}{}", prefix, &rest[length..]);
+    if !prefix.is_empty() && length < rest.len() {
+        return None;
+    }
+    Some(Parameter { name: rest[..length].to_string(), modifier })
+}
+
+/// Tree of the command, and what couldn't be read in it.
+pub fn parse(command: &str) -> Parsed {
+    let mut parser = Parser { source: command, position: 0, diagnostics: vec![], depth: 0, here_documents_end: None };
+    let list = parser.list(&[]);
+    Parsed { list, diagnostics: parser.diagnostics }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literal(text: &str) -> WordPart {
+        WordPart::Literal(text.into())
+    }
+
+    /// Words of the simple commands, as their text in the source.
+    fn commands(command: &str) -> Vec<Vec<&str>> {
+        let parsed = parse(command);
+        assert_eq!(parsed.diagnostics, [], "{}", command);
+        parsed
+            .list
+            .simple_commands()
+            .into_iter()
+            .map(|simple| simple.words.iter().map(|word| &command[word.span.clone()]).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_commands() {
+        for (command, expected) in [
+            ("ls -la", vec![vec!["ls", "-la"]]),
+            ("a | b && ! c || d & e; f\ng", vec![vec!["a"], vec!["b"], vec!["c"], vec!["d"], vec!["e"], vec!["f"], vec!["g"]]),
+            ("echo $(date +%s) `hostname`", vec![vec!["echo", "$(date +%s)", "`hostname`"], vec!["date", "+%s"], vec!["hostname"]]),
+            ("if test -f x; then cat x; elif true; then :; else echo no; fi", vec![
+                vec!["test", "-f", "x"],
+                vec!["cat", "x"],
+                vec!["true"],
+                vec![":"],
+                vec!["echo", "no"],
+            ]),
+            ("for f in *.rs; do wc -l \"$f\"; done", vec![vec!["wc", "-l", "\"$f\""]]),
+            ("while read l; do echo $l; done < in", vec![vec!["read", "l"], vec!["echo", "$l"]]),
+            ("case $1 in a|b) echo ab;; *) echo other;; esac", vec![vec!["echo", "ab"], vec!["echo", "other"]]),
+            ("f() { echo hi; }; (cd /tmp && ls)", vec![vec!["echo", "hi"], vec!["cd", "/tmp"], vec!["ls"]]),
+            ("cp <src> <dst> # copy <it>", vec![vec!["cp", "<src>", "<dst>"]]),
+            ("tar {{#if verbose}}-v{{/if}}czf {{archive | default \"a b\"}}", vec![vec!["tar", "{{#if verbose}}-v{{/if}}czf", "{{archive | default \"a b\"}}"]]),
+            ("echo a\\\nb", vec![vec!["echo", "a\\\nb"]]),
+        ] {
+            assert_eq!(commands(command), expected, "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_words() {
+        let parsed = parse("FOO=1 BAR= echo a\\ b'c d'\"$HOME/${x:-y}<name>\"$((1 + (2))) {{n | upper}} ${#x}");
+        assert_eq!(parsed.diagnostics, []);
+        let [simple] = parsed.list.simple_commands()[..] else {
+            panic!("expected one command");
+        };
+
+        let assignments: Vec<_> = simple.assignments.iter().map(|a| (a.name.as_str(), a.value.parts.clone())).collect();
+        assert_eq!(assignments, [("FOO", vec![literal("1")]), ("BAR", vec![])]);
+        assert_eq!(simple.name().as_deref(), Some("echo"));
+
+        let parts: Vec<_> = simple.words[1..].iter().map(|word| word.parts.clone()).collect();
+        assert_eq!(
+            parts,
+            [
+                vec![
+                    literal("a b"),
+                    WordPart::SingleQuoted("c d".into()),
+                    WordPart::DoubleQuoted(vec![
+                        WordPart::Parameter(Parameter { name: "HOME".into(), modifier: String::new() }),
+                        literal("/"),
+                        WordPart::Parameter(Parameter { name: "x".into(), modifier: ":-y".into() }),
+                        WordPart::Placeholder("name".into()),
+                    ]),
+                    WordPart::Arithmetic("1 + (2)".into()),
+                ],
+                vec![WordPart::Placeholder("n | upper".into())],
+                vec![WordPart::Parameter(Parameter { name: "x".into(), modifier: "#".into() })],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_redirections() {
+        let parsed = parse("sort < input > out 2>&1 3>>log <in-file>\ncat <<-EOF | wc -l; cat <<'END'\n\tone $x\nEOF\ntwo\nEND\necho done");
+        assert_eq!(parsed.diagnostics, []);
+        let simple = parsed.list.simple_commands();
+
+        let redirections: Vec<_> = simple[0]
+            .redirections
+            .iter()
+            .map(|redirection| (redirection.fd, redirection.redirect, redirection.target.literal().unwrap()))
+            .collect();
+        assert_eq!(
+            redirections,
+            [
+                (None, Redirect::Input, "input".to_string()),
+                (None, Redirect::Output, "out".into()),
+                (Some(2), Redirect::DuplicateOutput, "1".into()),
+                (Some(3), Redirect::Append, "log".into()),
+            ]
+        );
+        assert_eq!(simple[0].words[1].parts, [WordPart::Placeholder("in-file".into())]);
+
+        assert_eq!(simple[1].redirections[0].here_document.as_deref(), Some("one $x\n"));
+        assert_eq!(simple[3].redirections[0].here_document.as_deref(), Some("two\n"));
+        let names: Vec<_> = simple.iter().filter_map(|simple| simple.name()).collect();
+        assert_eq!(names, ["sort", "cat", "wc", "cat", "echo"]);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        for (command, message, range) in [
+            ("echo 'open", "`'` isn't closed", 5..6),
+            ("echo \"$(ls", "`$(` isn't closed, expected `)`", 6..8),
+            ("ls | | wc", "expected a command, found `|`", 5..6),
+            ("if true; then ls", "`if` isn't closed, expected `fi`", 0..2),
+            ("echo ${", "`${` isn't closed, expected `}`", 5..7),
+            ("echo ${%x}", "bad substitution", 5..10),
+            ("ls )", "unexpected `)`", 3..4),
+            ("cat > ", "expected a word after `>`", 4..5),
+            ("done", "unexpected `done`", 0..4),
+            ("cat <<EOF\nno end", "here-document isn't closed by `EOF`", 6..9),
+            ("for 1 in a; do :; done", "expected the name of the variable after `for`", 4..5),
+        ] {
+            let parsed = parse(command);
+            assert_eq!(parsed.diagnostics.first(), Some(&Diagnostic { range, message: message.into() }), "{}", command);
+        }
+
+        // The commands after the error are still read.
+        let parsed = parse("ls )\necho ok");
+        assert_eq!(parsed.list.simple_commands().last().and_then(|simple| simple.name()).as_deref(), Some("echo"));
+    }
+
+    #[test]
+    fn test_never_panics() {
+        let commands = [
+            "for f in $(ls <dir>); do { echo \"${f%.*}\" >&2; } || (exit 1) & done",
+            "case \"$x\" in (a*|'b') cat <<E\nbody\nE\n;; *) : ;; esac # end",
+            "f() ( echo `date \\`x\\``; ) && ! echo $((1+ )) ${x:-{a}} {{#if y}}{{/if}}",
+            "é'ü\"\\\n$(((",
+        ];
+        for command in commands {
+            for (end, _) in command.char_indices() {
+                parse(&command[..end]);
+                parse(&command[end..]);
+            }
+        }
+
+        let nested = "$(".repeat(1000);
+        assert!(parse(&nested).diagnostics.iter().any(|diagnostic| diagnostic.message == "commands are nested too deeply"));
+    }
+}
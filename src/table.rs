@@ -1,9 +1,111 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+
 /// Table for the commands present in the application,
 /// it will work as a bridge between the UI and the search thread, in a way that the search thread will update
 /// it regularly with the commands found and the UI will render it.
-/// 
+///
 /// This table will be shared between different threads, so it needs to provide a safe way to access and update it.
+/// Publishers build a whole new [`Snapshot`] and swap it in, readers get the latest one without taking any lock,
+/// so the draw loop never waits for the search thread.
+#[derive(Debug)]
+pub struct Table<T> {
+    current: ArcSwap<Snapshot<T>>,
+    next_version: AtomicU64,
+}
+
+/// Result set published to the [`Table`], versions only grow.
+#[derive(Debug)]
+pub struct Snapshot<T> {
+    pub version: u64,
+    pub rows: Vec<T>,
+}
+
+/// Tells whether two rows are the same item, even if they come from different result sets.
+pub trait Identity {
+    fn is(&self, other: &Self) -> bool;
+}
+
+impl<T> Default for Table<T> {
+    fn default() -> Self {
+        Self {
+            current: ArcSwap::from_pointee(Snapshot { version: 0, rows: vec![] }),
+            next_version: AtomicU64::new(1),
+        }
+    }
+}
+
+impl<T> Table<T> {
+    /// Publishes a new result set, returning its version.
+    ///
+    /// When several threads publish at the same time the newest version wins, a slow publisher
+    /// never replaces a result set that was published after it got its version.
+    pub fn publish(&self, rows: Vec<T>) -> u64 {
+        let version = self.next_version.fetch_add(1, Ordering::Relaxed);
+        let snapshot = Arc::new(Snapshot { version, rows });
+
+        self.current.rcu(|current| {
+            if current.version > version {
+                current.clone()
+            } else {
+                snapshot.clone()
+            }
+        });
+
+        version
+    }
+
+    /// Latest result set published.
+    pub fn load(&self) -> Arc<Snapshot<T>> {
+        self.current.load_full()
+    }
+
+    /// Version of the latest result set published.
+    pub fn version(&self) -> u64 {
+        self.current.load().version
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish() {
+        let table = Table::default();
+        assert_eq!(table.version(), 0);
+        assert!(table.load().rows.is_empty());
+
+        let first = table.publish(vec![1, 2, 3]);
+        let second = table.publish(vec![4]);
+        assert!(second > first);
+        assert_eq!(table.version(), second);
+        assert_eq!(table.load().rows, vec![4]);
+    }
+
+    #[test]
+    fn test_publish_concurrently() {
+        let table = Arc::new(Table::default());
+
+        let publishers = (0..8)
+            .map(|publisher| {
+                let table = table.clone();
+                std::thread::spawn(move || {
+                    for row in 0..100 {
+                        let version = table.publish(vec![publisher * 100 + row]);
+                        // Readers never go back to an older result set.
+                        assert!(table.version() >= version);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for publisher in publishers {
+            publisher.join().unwrap();
+        }
 
-pub struct Table {
-    
-}
\ No newline at end of file
+        assert_eq!(table.version(), 800);
+    }
+}
@@ -0,0 +1,568 @@
+//! Placeholders of the commands, written `<name>` as in navi, and a small template language on top:
+//!
+//! ```text
+//! tar {{#if verbose}}-v{{/if}}czf {{archive | default "backup.tgz"}} {{folder | shellquote}}
+//! ```
+//!
+//! - `{{name}}` is replaced by the value of the variable, like `<name>`, after its filters.
+//! - `{{#if name}}…{{else}}…{{/if}}` keeps the first part if the variable has a value, the second one otherwise.
+//! - Filters are `default "text"`, used when there is no value, `upper`, `lower`, `basename`, `shellquote`
+//!   and `raw`.
+//!
+//! Commands with tags that aren't written in this language, like the `{{.State.Status}}` of Go templates
+//! given to `docker inspect --format`, are left as text, only their `<name>` placeholders are replaced.
+//!
+//! Values are quoted for the [`Shell`], taking into account the quotes open around them, so
+//! `git commit -m "<message>"` works with any message. `raw` leaves the value as typed, for lists of flags.
+
+use std::collections::HashMap;
+
+use crate::cheat::is_variable_char;
+use crate::shell::{Quoting, Shell};
+
+/// Piece of a command, either text written as is or a placeholder to fill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Piece<'a> {
+    Text(&'a str),
+    /// Name of the placeholder, without the angle brackets.
+    Placeholder(&'a str),
+}
+
+/// Splits the command in text and placeholders.
+///
+/// Angle brackets around anything that isn't a variable name are text, so redirections
+/// like `sort < input > output` are left alone.
+pub fn pieces(command: &str) -> Vec<Piece<'_>> {
+    let mut pieces = vec![];
+    let mut text = 0;
+    let mut rest = 0;
+
+    while let Some(open) = command[rest..].find('<').map(|open| rest + open) {
+        let name = &command[open + 1..];
+        let length = name.find(|c: char| !is_variable_char(c)).unwrap_or(name.len());
+
+        if length > 0 && name[length..].starts_with('>') {
+            if text < open {
+                pieces.push(Piece::Text(&command[text..open]));
+            }
+            pieces.push(Piece::Placeholder(&name[..length]));
+            text = open + length + 2;
+            rest = text;
+        } else {
+            rest = open + 1;
+        }
+    }
+
+    if text < command.len() {
+        pieces.push(Piece::Text(&command[text..]));
+    }
+
+    pieces
+}
+
+/// Node of a parsed command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+    /// `{{name | filter | ...}}`
+    Expression { name: &'a str, filters: Vec<Filter> },
+    /// `{{#if name}}then{{else}}otherwise{{/if}}`
+    If { name: &'a str, then: Vec<Node<'a>>, otherwise: Vec<Node<'a>> },
+}
+
+/// Transformation of a value in an expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// Text used when the variable has no value.
+    Default(String),
+    Upper,
+    Lower,
+    /// Last component of a path.
+    Basename,
+    /// Quoted as a single word, even if it is empty.
+    Shellquote,
+    /// Not quoted at all.
+    Raw,
+}
+
+impl Filter {
+    fn parse(filter: &str) -> Result<Self, String> {
+        let words = shell_words::split(filter).map_err(|_| "unterminated quote".to_string())?;
+        let (name, arguments) = words.split_first().ok_or("missing filter after `|`")?;
+
+        let filter = match (name.as_str(), arguments) {
+            ("default", [text]) => Filter::Default(text.clone()),
+            ("default", _) => return Err("`default` takes a single text, like `default \"value\"`".to_string()),
+            ("upper", []) => Filter::Upper,
+            ("lower", []) => Filter::Lower,
+            ("basename", []) => Filter::Basename,
+            ("shellquote", []) => Filter::Shellquote,
+            ("raw", []) => Filter::Raw,
+            ("upper" | "lower" | "basename" | "shellquote" | "raw", _) => return Err(format!("`{}` takes no arguments", name)),
+            (name, _) => return Err(format!("unknown filter `{}`", name)),
+        };
+
+        Ok(filter)
+    }
+
+    /// Applies the filter to the value, `None` when the variable has no value yet.
+    fn apply(&self, value: Option<String>, shell: Shell) -> Option<String> {
+        match self {
+            Filter::Default(text) => match value {
+                Some(value) if !value.is_empty() => Some(value),
+                _ => Some(text.clone()),
+            },
+            Filter::Upper => value.map(|value| value.to_uppercase()),
+            Filter::Lower => value.map(|value| value.to_lowercase()),
+            Filter::Basename => value.map(|value| basename(&value).to_string()),
+            Filter::Shellquote => value.map(|value| shell.quote(&value)),
+            Filter::Raw => value,
+        }
+    }
+}
+
+/// Last component of the path, trailing slashes aside.
+fn basename(path: &str) -> &str {
+    let trimmed = path.trim_end_matches('/');
+    match trimmed.rfind('/') {
+        Some(slash) => &trimmed[slash + 1..],
+        None if trimmed.is_empty() && !path.is_empty() => "/",
+        None => trimmed,
+    }
+}
+
+/// Error in the template of a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateError {
+    /// Byte offset in the command where the error is.
+    pub offset: usize,
+    pub message: String,
+}
+
+impl TemplateError {
+    fn new(offset: usize, message: impl Into<String>) -> Self {
+        Self { offset, message: message.into() }
+    }
+
+    /// Line and column of the error, both starting at 0, the column counted in characters.
+    pub fn position(&self, command: &str) -> (usize, usize) {
+        let before = &command[..self.offset.min(command.len())];
+        let line = before.matches('\n').count();
+        let column = before.rsplit('\n').next().unwrap_or_default().chars().count();
+        (line, column)
+    }
+}
+
+/// Parsed command, see [`parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template<'a> {
+    nodes: Vec<Node<'a>>,
+}
+
+/// Open `{{#if}}` block while parsing.
+struct Block<'a> {
+    name: &'a str,
+    offset: usize,
+    then: Vec<Node<'a>>,
+    /// Nodes after `{{else}}`, once it is found.
+    otherwise: Option<Vec<Node<'a>>>,
+}
+
+impl<'a> Block<'a> {
+    fn nodes(&mut self) -> &mut Vec<Node<'a>> {
+        self.otherwise.as_mut().unwrap_or(&mut self.then)
+    }
+}
+
+/// Checks the name of a variable used in a tag starting at `offset`.
+fn variable(name: &str, offset: usize) -> Result<&str, TemplateError> {
+    match name {
+        "" => Err(TemplateError::new(offset, "missing variable name")),
+        name if !name.chars().all(is_variable_char) => {
+            Err(TemplateError::new(offset, format!("invalid variable name `{}`", name)))
+        },
+        name => Ok(name),
+    }
+}
+
+/// Splits an expression on the `|` outside quotes, with the offset of each part.
+fn split_filters(expression: &str) -> Vec<(usize, &str)> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut quote = None;
+
+    for (index, c) in expression.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            ('|', None) => {
+                parts.push((start, &expression[start..index]));
+                start = index + 1;
+            },
+            _ => {},
+        }
+    }
+    parts.push((start, &expression[start..]));
+
+    parts
+}
+
+/// Whether the tag is written in the template language: a block or an expression on a variable.
+fn is_tag(tag: &str) -> bool {
+    match tag.split_once(char::is_whitespace).unwrap_or((tag, "")) {
+        ("#if", _) | ("else" | "/if", "") => true,
+        _ => {
+            let name = split_filters(tag)[0].1.trim();
+            !name.is_empty() && name.chars().all(is_variable_char)
+        },
+    }
+}
+
+/// Whether the command has a closed tag that isn't written in the template language.
+fn is_foreign(command: &str) -> bool {
+    let mut rest = command;
+    while let Some((_, after)) = rest.split_once("{{") {
+        let Some((tag, after)) = after.split_once("}}") else {
+            return false;
+        };
+        if !is_tag(tag.trim()) {
+            return true;
+        }
+        rest = after;
+    }
+    false
+}
+
+/// Nodes of the innermost open block, or the top ones.
+fn current<'a, 'b>(blocks: &'b mut [Block<'a>], nodes: &'b mut Vec<Node<'a>>) -> &'b mut Vec<Node<'a>> {
+    match blocks.last_mut() {
+        Some(block) => block.nodes(),
+        None => nodes,
+    }
+}
+
+/// Adds the text between tags, with its `<name>` placeholders.
+fn push_text<'a>(nodes: &mut Vec<Node<'a>>, text: &'a str) {
+    nodes.extend(pieces(text).into_iter().map(|piece| match piece {
+        Piece::Text(text) => Node::Text(text),
+        Piece::Placeholder(name) => Node::Placeholder(name),
+    }));
+}
+
+/// Parses the template language of the command.
+pub fn parse(command: &str) -> Result<Template<'_>, TemplateError> {
+    if is_foreign(command) {
+        return Ok(Template::plain(command));
+    }
+
+    let mut nodes = vec![];
+    let mut blocks: Vec<Block> = vec![];
+    let mut rest = 0;
+
+    while let Some(open) = command[rest..].find("{{").map(|open| rest + open) {
+        push_text(current(&mut blocks, &mut nodes), &command[rest..open]);
+
+        let close = command[open..]
+            .find("}}")
+            .map(|close| open + close)
+            .ok_or_else(|| TemplateError::new(open, "unclosed `{{`"))?;
+        let tag = &command[open + 2..close];
+        let start = open + 2 + (tag.len() - tag.trim_start().len());
+        let tag = tag.trim();
+        rest = close + 2;
+
+        let node = match tag.split_once(char::is_whitespace).unwrap_or((tag, "")) {
+            ("#if", name) => {
+                let name = variable(name.trim(), start)?;
+                blocks.push(Block { name, offset: open, then: vec![], otherwise: None });
+                continue;
+            },
+            ("else", "") => match blocks.last_mut() {
+                Some(block) if block.otherwise.is_none() => {
+                    block.otherwise = Some(vec![]);
+                    continue;
+                },
+                Some(_) => return Err(TemplateError::new(open, "`{{else}}` used twice in the same `{{#if}}`")),
+                None => return Err(TemplateError::new(open, "`{{else}}` outside of `{{#if}}`")),
+            },
+            ("/if", "") => match blocks.pop() {
+                Some(block) => Node::If {
+                    name: block.name,
+                    then: block.then,
+                    otherwise: block.otherwise.unwrap_or_default(),
+                },
+                None => return Err(TemplateError::new(open, "`{{/if}}` without `{{#if}}`")),
+            },
+            _ => {
+                let mut parts = split_filters(tag).into_iter();
+                let (_, name) = parts.next().unwrap_or_default();
+                let name = variable(name.trim(), start)?;
+                let filters = parts
+                    .map(|(offset, filter)| {
+                        let offset = start + offset + (filter.len() - filter.trim_start().len());
+                        Filter::parse(filter).map_err(|message| TemplateError::new(offset, message))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Node::Expression { name, filters }
+            },
+        };
+
+        current(&mut blocks, &mut nodes).push(node);
+    }
+    push_text(current(&mut blocks, &mut nodes), &command[rest..]);
+
+    if let Some(block) = blocks.pop() {
+        return Err(TemplateError::new(block.offset, format!("unclosed `{{{{#if {}}}}}`", block.name)));
+    }
+
+    Ok(Template { nodes })
+}
+
+/// Piece of an evaluated command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Output<'a> {
+    Text(&'a str),
+    /// Value of a variable, after its filters.
+    Value { name: &'a str, text: String },
+    /// Variable without a value yet.
+    Missing(&'a str),
+}
+
+impl Output<'_> {
+    /// Text of the piece, missing variables are written as placeholders.
+    pub fn text(&self) -> String {
+        match self {
+            Output::Text(text) => text.to_string(),
+            Output::Value { text, .. } => text.clone(),
+            Output::Missing(name) => format!("<{}>", name),
+        }
+    }
+}
+
+impl<'a> Template<'a> {
+    /// Names of the variables used, in order of appearance and without repetitions.
+    pub fn variables(&self) -> Vec<&'a str> {
+        fn collect<'a>(nodes: &[Node<'a>], names: &mut Vec<&'a str>) {
+            for node in nodes {
+                let name = match node {
+                    Node::Text(_) => continue,
+                    Node::Placeholder(name) | Node::Expression { name, .. } => name,
+                    Node::If { name, then, otherwise } => {
+                        if !names.contains(name) {
+                            names.push(name);
+                        }
+                        collect(then, names);
+                        collect(otherwise, names);
+                        continue;
+                    },
+                };
+                if !names.contains(name) {
+                    names.push(name);
+                }
+            }
+        }
+
+        let mut names = vec![];
+        collect(&self.nodes, &mut names);
+        names
+    }
+
+    /// Evaluates the template with the values given, variables without one are missing.
+    ///
+    /// Values are quoted for the shell, empty ones are left empty.
+    pub fn evaluate(&self, values: &HashMap<String, String>, shell: Shell) -> Vec<Output<'a>> {
+        let mut output = vec![];
+        evaluate(&self.nodes, values, shell, &mut Quoting::None, &mut output);
+        output
+    }
+
+    /// Command with the values given, variables without one are kept as placeholders.
+    pub fn render(&self, values: &HashMap<String, String>, shell: Shell) -> String {
+        self.evaluate(values, shell).iter().map(Output::text).collect()
+    }
+
+    /// Template with the `<name>` placeholders of the command only, for commands with template errors.
+    fn plain(command: &'a str) -> Self {
+        let mut nodes = vec![];
+        push_text(&mut nodes, command);
+        Self { nodes }
+    }
+}
+
+fn evaluate<'a>(
+    nodes: &[Node<'a>],
+    values: &HashMap<String, String>,
+    shell: Shell,
+    quoting: &mut Quoting,
+    output: &mut Vec<Output<'a>>,
+) {
+    let quote = |value: String, quoting: Quoting| {
+        if value.is_empty() { value } else { shell.quote_in(&value, quoting) }
+    };
+
+    for node in nodes {
+        match node {
+            Node::Text(text) => {
+                *quoting = shell.scan(text, *quoting);
+                output.push(Output::Text(text));
+            },
+            Node::Placeholder(name) => output.push(match values.get(*name) {
+                Some(value) => Output::Value { name, text: quote(value.clone(), *quoting) },
+                None => Output::Missing(name),
+            }),
+            Node::Expression { name, filters } => {
+                let value = filters
+                    .iter()
+                    .fold(values.get(*name).cloned(), |value, filter| filter.apply(value, shell));
+                let quoted = filters.iter().any(|filter| matches!(filter, Filter::Shellquote | Filter::Raw));
+                output.push(match value {
+                    Some(text) if quoted => Output::Value { name, text },
+                    Some(text) => Output::Value { name, text: quote(text, *quoting) },
+                    None => Output::Missing(name),
+                });
+            },
+            Node::If { name, then, otherwise } => {
+                let set = values.get(*name).is_some_and(|value| !value.is_empty());
+                evaluate(if set { then } else { otherwise }, values, shell, quoting, output);
+            },
+        }
+    }
+}
+
+/// Names of the variables of the command, in order of appearance and without repetitions.
+///
+/// Commands with template errors only have their `<name>` placeholders.
+pub fn placeholders(command: &str) -> Vec<&str> {
+    parse(command).unwrap_or_else(|_| Template::plain(command)).variables()
+}
+
+/// Replaces the variables with their values quoted for the shell, the ones without a value are kept
+/// as placeholders.
+///
+/// Commands with template errors only have their `<name>` placeholders replaced.
+pub fn render(command: &str, values: &HashMap<String, String>, shell: Shell) -> String {
+    parse(command).unwrap_or_else(|_| Template::plain(command)).render(values, shell)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pieces() {
+        assert_eq!(pieces("docker logs <container> -f"), vec![
+            Piece::Text("docker logs "),
+            Piece::Placeholder("container"),
+            Piece::Text(" -f"),
+        ]);
+        assert_eq!(pieces("<a><b_c>"), vec![Piece::Placeholder("a"), Piece::Placeholder("b_c")]);
+        assert_eq!(pieces("sort < in > out"), vec![Piece::Text("sort < in > out")]);
+        assert_eq!(pieces("cat <<EOF <file>"), vec![Piece::Text("cat <<EOF "), Piece::Placeholder("file")]);
+        assert_eq!(pieces("<>"), vec![Piece::Text("<>")]);
+        assert_eq!(pieces(""), vec![]);
+    }
+
+    #[test]
+    fn test_render() {
+        let command = "cp <src> <dst> && ls <dst>";
+        assert_eq!(placeholders(command), vec!["src", "dst"]);
+
+        let values = HashMap::from([("dst".to_string(), "/tmp".to_string())]);
+        assert_eq!(render(command, &values, Shell::Sh), "cp <src> /tmp && ls /tmp");
+    }
+
+    #[test]
+    fn test_quoting() {
+        for (command, value, shell, rendered) in [
+            ("git commit -m <message>", "say \"hi\"", Shell::Bash, "git commit -m 'say \"hi\"'"),
+            ("git commit -m \"<message>\"", "say \"hi\" to $USER", Shell::Bash, "git commit -m \"say \\\"hi\\\" to \\$USER\""),
+            ("echo '<message>'", "it's", Shell::Sh, "echo 'it'\\''s'"),
+            ("echo <message>", "it's", Shell::Fish, "echo 'it\\'s'"),
+            ("echo \"<message>\"", "$env:HOME", Shell::PowerShell, "echo \"`$env:HOME\""),
+            ("ls {{message | raw}}", "-l -a", Shell::Bash, "ls -l -a"),
+            ("ls {{message}}", "-l -a", Shell::Bash, "ls '-l -a'"),
+            ("ls <message> 'a' \\' \"<message>\"", "a b", Shell::Bash, "ls 'a b' 'a' \\' \"a b\""),
+            ("touch {{message | default \"new file\"}}", "", Shell::Zsh, "touch 'new file'"),
+        ] {
+            let values = HashMap::from([("message".to_string(), value.to_string())]);
+            assert_eq!(render(command, &values, shell), rendered, "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_template() {
+        let command = "tar {{#if verbose}}-v{{/if}}czf {{archive | default \"backup.tgz\"}} <folder>";
+        assert_eq!(placeholders(command), vec!["verbose", "archive", "folder"]);
+
+        let values = |pairs: &[(&str, &str)]| {
+            pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect::<HashMap<_, _>>()
+        };
+        for (pairs, rendered) in [
+            (&[][..], "tar czf backup.tgz <folder>"),
+            (&[("verbose", "yes"), ("folder", "src")][..], "tar -vczf backup.tgz src"),
+            (&[("verbose", ""), ("archive", "src.tgz")][..], "tar czf src.tgz <folder>"),
+        ] {
+            assert_eq!(render(command, &values(pairs), Shell::Sh), rendered, "{:?}", pairs);
+        }
+
+        for (command, rendered) in [
+            ("{{name | upper}}", "'MY FILE/'"),
+            ("{{name | basename | lower}}", "'my file'"),
+            ("{{ name | shellquote }}", "'my File/'"),
+            ("{{#if name}}{{#if other}}both{{else}}one{{/if}}{{else}}none{{/if}}", "one"),
+            ("{{other | upper}}", "<other>"),
+            ("{{other | default 'a | b' | upper | raw}}", "A | B"),
+        ] {
+            assert_eq!(render(command, &values(&[("name", "my File/")]), Shell::Sh), rendered, "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_filters() {
+        for (path, name) in [("/tmp/a.txt", "a.txt"), ("src/", "src"), ("file", "file"), ("/", "/")] {
+            assert_eq!(basename(path), name, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_foreign_tags() {
+        for command in [
+            "docker inspect --format '{{.State.Status}}' <container>",
+            "kubectl get pods -o go-template='{{range .items}}{{.metadata.name}}{{\"\\n\"}}{{end}}'",
+            "helm template . --set x='{{ include \"a\" . | nindent 4 }}'",
+            "{{#each x}}{{}}{{ a b }}{{/* comment */}}",
+        ] {
+            let template = parse(command).unwrap();
+            assert_eq!(template.render(&HashMap::new(), Shell::Sh), command, "{}", command);
+        }
+
+        // Only the `<name>` placeholders are replaced, the rest is for the command.
+        let command = "docker inspect -f '{{if .State.Running}}{{.Name}}{{else}}-{{end}}' <container> {{container}}";
+        assert_eq!(placeholders(command), vec!["container"]);
+        let values = HashMap::from([("container".to_string(), "my web".to_string())]);
+        assert_eq!(
+            render(command, &values, Shell::Sh),
+            "docker inspect -f '{{if .State.Running}}{{.Name}}{{else}}-{{end}}' 'my web' {{container}}"
+        );
+    }
+
+    #[test]
+    fn test_template_errors() {
+        for (command, position, message) in [
+            ("echo {{name", (0, 5), "unclosed `{{`"),
+            ("echo {{#if x}}\n-v", (0, 5), "unclosed `{{#if x}}`"),
+            ("echo\n  {{/if}}", (1, 2), "`{{/if}}` without `{{#if}}`"),
+            ("{{#if x}}{{else}}{{else}}{{/if}}", (0, 17), "`{{else}}` used twice in the same `{{#if}}`"),
+            ("{{else}}", (0, 0), "`{{else}}` outside of `{{#if}}`"),
+            ("{{#if a b}}{{/if}}", (0, 2), "invalid variable name `a b`"),
+            ("{{#if}}{{/if}}", (0, 2), "missing variable name"),
+            ("ls {{dir | reverse}}", (0, 11), "unknown filter `reverse`"),
+            ("{{dir | default}}", (0, 8), "`default` takes a single text, like `default \"value\"`"),
+            ("{{dir |}}", (0, 7), "missing filter after `|`"),
+        ] {
+            let err = parse(command).unwrap_err();
+            assert_eq!((err.position(command), err.message.as_str()), (position, message), "{}", command);
+        }
+    }
+}
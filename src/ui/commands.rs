@@ -1,51 +1,233 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
 use ratatui::widgets::Row;
 
-use ratatui::style::{Color, Stylize};
-use ratatui::text::Text;
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use unicode_segmentation::UnicodeSegmentation;
 
-#[derive(Debug, Clone)]
+use crate::search::{Hit, MatchKind};
+use crate::table::Identity;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Command{
     pub command: String,
-    pub tags: Vec<String>, 
-    pub description: String
-}
-
-impl<'a> Into<Row<'a>> for Command {
-    fn into(self) -> Row<'a> {
-        let tags =  if self.tags.is_empty() {
-            Text::from("No tags").fg(Color::Indexed(75))
-        } else {
-            let tags = self.tags.join(", ");
-            Text::from(tags)
-                .bold()
-                .fg(Color::Indexed(220))
-        };
+    pub tags: Vec<String>,
+    pub description: String,
+    /// Variables declared with `$ name: ...` in the same block of the cheat file.
+    pub variables: Vec<Variable>,
+    /// Where the command was read from, if it comes from a cheat file.
+    pub source: Option<Source>,
+}
+
+/// Variable declared in a cheat file with the `$ name: generator --- options` syntax.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Variable {
+    pub name: String,
+    /// Shell command whose output lines are the suggested values for the variable.
+    pub generator: Option<String>,
+    pub kind: VariableType,
+    /// Value the input starts with.
+    pub default: Option<String>,
+    /// Whether the command can be accepted with the variable empty.
+    pub required: bool,
+}
+
+/// Values a variable accepts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum VariableType {
+    #[default]
+    String,
+    Integer,
+    Path,
+    /// Path of a folder, only folders are offered.
+    Directory,
+    /// One of the choices, picked from a list.
+    Enum(Vec<String>),
+    /// Values matching the regular expression.
+    Regex(Pattern),
+}
+
+/// Regular expression of a variable, compiled once when the cheat file is read.
+#[derive(Debug, Clone)]
+pub struct Pattern(pub regex::Regex);
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Eq for Pattern {}
+
+impl Variable {
+    /// Checks the value against the type of the variable, the error says what is expected.
+    ///
+    /// Empty values are only checked against the `required` flag.
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        if value.is_empty() {
+            return if self.required { Err("a value is required".to_string()) } else { Ok(()) };
+        }
+
+        match &self.kind {
+            VariableType::String | VariableType::Path | VariableType::Directory => Ok(()),
+            VariableType::Integer => value
+                .parse::<i64>()
+                .map(|_| ())
+                .map_err(|_| "must be an integer".to_string()),
+            VariableType::Enum(choices) if choices.iter().any(|choice| choice == value) => Ok(()),
+            VariableType::Enum(choices) => Err(format!("must be one of {}", choices.join(", "))),
+            VariableType::Regex(Pattern(regex)) if regex.is_match(value) => Ok(()),
+            VariableType::Regex(Pattern(regex)) => Err(format!("must match `{}`", regex.as_str())),
+        }
+    }
+}
+
+/// Location of a command inside a cheat file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Source {
+    pub path: PathBuf,
+    /// Line (1-based) where the command body starts.
+    pub line: usize,
+}
+
+impl Identity for Command {
+    fn is(&self, other: &Self) -> bool {
+        match (&self.source, &other.source) {
+            (Some(source), Some(other)) => source == other,
+            _ => self.command == other.command && self.description == other.description,
+        }
+    }
+}
 
-        let command = Text::from(self.command)
+/// Style added to the graphemes that matched the query.
+const MATCHED: Style = Style::new().fg(Color::Indexed(203)).add_modifier(Modifier::UNDERLINED);
+
+/// Splits the text in spans, the graphemes whose position is in `indices` get the [`MATCHED`] style.
+///
+/// Positions are counted from `offset`, the position of the first grapheme of the text in the matched one.
+fn highlight<'a>(text: &str, offset: usize, indices: &[u32]) -> Vec<Span<'a>> {
+    let mut spans = vec![];
+    let mut current = String::new();
+    let mut matched = false;
+
+    for (n, grapheme) in text.graphemes(true).enumerate() {
+        let is_matched = indices.binary_search(&((offset + n) as u32)).is_ok();
+        if is_matched != matched && !current.is_empty() {
+            let content = std::mem::take(&mut current);
+            spans.push(if matched { Span::styled(content, MATCHED) } else { Span::raw(content) });
+        }
+        matched = is_matched;
+        current.push_str(grapheme);
+    }
+
+    if !current.is_empty() {
+        spans.push(if matched { Span::styled(current, MATCHED) } else { Span::raw(current) });
+    }
+
+    spans
+}
+
+/// Row of the table for the command, highlighting the graphemes at `indices`.
+///
+/// The positions are the ones in the text the matcher sees: command, tags and description separated by a space.
+fn row<'a>(command: &Command, indices: &[u32]) -> Row<'a> {
+    let length = |text: &str| text.graphemes(true).count();
+    let tags_offset = length(&command.command) + 1;
+    let description_offset = tags_offset + length(&command.tags.join(" ")) + 1;
+
+    let tags = if command.tags.is_empty() {
+        Text::from("No tags").fg(Color::Indexed(75))
+    } else {
+        let mut spans = vec![];
+        let mut offset = tags_offset;
+        for (n, tag) in command.tags.iter().enumerate() {
+            if n > 0 {
+                spans.push(Span::raw(", "));
+            }
+            spans.extend(highlight(tag, offset, indices));
+            offset += length(tag) + 1;
+        }
+
+        Text::from(Line::from(spans))
             .bold()
-            .fg(Color::Indexed(234));
+            .fg(Color::Indexed(220))
+    };
+
+    let description = Text::from(Line::from(highlight(&command.description, description_offset, indices)))
+        .fg(Color::Indexed(234));
 
-        let description = Text::from(self.description)
-            .fg(Color::Indexed(234));
+    let command = Text::from(Line::from(highlight(&command.command, 0, indices)))
+        .bold()
+        .fg(Color::Indexed(234));
 
-        Row::new(vec![command, tags, description])
+    Row::new(vec![command, tags, description])
+}
+
+impl<'a> From<Command> for Row<'a> {
+    fn from(command: Command) -> Self {
+        row(&command, &[])
     }
 }
 
-impl From<Vec<String>> for Command {
-    fn from(mut vec: Vec<String>) -> Self {
-        let command = vec.pop().unwrap_or_default();
-        let description = vec
-            .last()
-            .cloned()
-            .unwrap_or_default();
+impl<'a> From<Hit> for Row<'a> {
+    fn from(hit: Hit) -> Self {
+        match hit.kind {
+            MatchKind::Fuzzy => row(&hit.command, &hit.indices()),
+            // Only sounds like the query, so it is dimmed and marked to tell it apart.
+            MatchKind::Phonetic => {
+                let mut command = Arc::unwrap_or_clone(hit.command);
+                command.command = format!("≈ {}", command.command);
+                Row::from(command).italic()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        vec.truncate(vec.len() - 1); 
-            
-        Self {
-            command, 
-            tags: vec, 
-            description
+    #[test]
+    fn test_validate() {
+        let variable = |kind: VariableType, required: bool| Variable {
+            name: "x".into(),
+            kind,
+            required,
+            ..Default::default()
+        };
+
+        for (variable, value, valid) in [
+            (variable(VariableType::String, false), "", true),
+            (variable(VariableType::String, true), "", false),
+            (variable(VariableType::Integer, true), "-42", true),
+            (variable(VariableType::Integer, true), "4.2", false),
+            (variable(VariableType::Integer, false), "", true),
+            (variable(VariableType::Enum(vec!["dev".into(), "prod".into()]), true), "prod", true),
+            (variable(VariableType::Enum(vec!["dev".into(), "prod".into()]), true), "pro", false),
+            (variable(VariableType::Regex(Pattern(regex::Regex::new("^v[0-9]+$").unwrap())), true), "v12", true),
+            (variable(VariableType::Regex(Pattern(regex::Regex::new("^v[0-9]+$").unwrap())), true), "12", false),
+            (variable(VariableType::Path, true), "~/src", true),
+        ] {
+            assert_eq!(variable.validate(value).is_ok(), valid, "{:?} {}", variable.kind, value);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_highlight() {
+        let spans = highlight("git log", 0, &[0, 1, 4]);
+        assert_eq!(spans, vec![
+            Span::styled("gi", MATCHED),
+            Span::raw("t "),
+            Span::styled("l", MATCHED),
+            Span::raw("og"),
+        ]);
+
+        // Graphemes are counted, not bytes.
+        let spans = highlight("añb", 10, &[11]);
+        assert_eq!(spans, vec![Span::raw("a"), Span::styled("ñ", MATCHED), Span::raw("b")]);
+
+        assert_eq!(highlight("", 0, &[0]), vec![]);
+    }
+}
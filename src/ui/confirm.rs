@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+
+use crate::danger::Risk;
+use crate::ui::template::Accepted;
+
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+
+/// Command accepted in the form that needs to be confirmed, for the risks found in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Confirmation {
+    pub accepted: Accepted,
+    pub risks: Vec<Risk>,
+}
+
+/// Command with the risky parts highlighted, line by line.
+fn highlight(command: &str, risks: &[Risk]) -> Text<'static> {
+    let risky = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    let is_risky = |offset: usize| risks.iter().filter_map(|risk| risk.range.as_ref()).any(|range| range.contains(&offset));
+    let span = |text: String, highlighted: bool| if highlighted { Span::styled(text, risky) } else { Span::raw(text) };
+
+    let mut lines = vec![];
+    let mut offset = 0;
+    for line in command.split('\n') {
+        let (mut spans, mut piece, mut highlighted) = (vec![], String::new(), false);
+        for (index, c) in line.char_indices() {
+            if is_risky(offset + index) != highlighted && !piece.is_empty() {
+                spans.push(span(std::mem::take(&mut piece), highlighted));
+            }
+            highlighted = is_risky(offset + index);
+            piece.push(c);
+        }
+        if !piece.is_empty() {
+            spans.push(span(piece, highlighted));
+        }
+        lines.push(Line::from(spans));
+        offset += line.len() + 1;
+    }
+    Text::from(lines)
+}
+
+impl Confirmation {
+    /// Reasons of the risks, each one once in the order they are found.
+    fn reasons(&self) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        self.risks.iter().map(|risk| risk.reason.as_str()).filter(|reason| seen.insert(*reason)).collect()
+    }
+
+    /// Draws the confirmation over the middle of the area.
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let verb = if self.accepted.execute { "run" } else { "print" };
+
+        let mut text = highlight(&self.accepted.rendered, &self.risks);
+        text.push_line(Line::default());
+        for reason in self.reasons() {
+            text.push_line(Line::from(vec![Span::styled("⚠ ", Style::default().fg(Color::Red)), Span::raw(reason.to_string())]));
+        }
+
+        let height = (text.height() as u16 + 2).min(area.height);
+        let [popup] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(area);
+        let [popup] = Layout::horizontal([Constraint::Percentage(70)]).flex(Flex::Center).areas(popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Red))
+            .title(format!("Dangerous command, {} it anyway?", verb))
+            .title_style(Style::default().add_modifier(Modifier::BOLD))
+            .title_bottom(format!("Enter or y to {}, Esc or n to go back", verb));
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }).block(block), popup);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    use crate::ui::commands::Command;
+
+    #[test]
+    fn test_highlight() {
+        let risks = [
+            Risk { reason: "tagged danger".into(), range: None },
+            Risk { reason: "a".into(), range: Some(3..8) },
+            Risk { reason: "b".into(), range: Some(6..11) },
+            Risk { reason: "c".into(), range: Some(13..15) },
+        ];
+        let text = highlight("ls rm -rf /\nmkfs x", &risks);
+
+        let pieces: Vec<Vec<(&str, bool)>> = text
+            .lines
+            .iter()
+            .map(|line| line.spans.iter().map(|span| (span.content.as_ref(), span.style != Style::default())).collect())
+            .collect();
+        assert_eq!(pieces, vec![vec![("ls ", false), ("rm -rf /", true)], vec![("m", false), ("kf", true), ("s x", false)]]);
+    }
+
+    #[test]
+    fn test_reasons() {
+        let confirmation = Confirmation {
+            accepted: Accepted {
+                command: Command {
+                    command: "rm -rf / && rm -rf ~".into(),
+                    tags: vec![],
+                    description: String::new(),
+                    variables: vec![],
+                    source: None,
+                },
+                values: HashMap::new(),
+                rendered: "rm -rf / && rm -rf ~".into(),
+                execute: false,
+            },
+            risks: vec![
+                Risk { reason: "a".into(), range: Some(0..2) },
+                Risk { reason: "b".into(), range: Some(3..5) },
+                Risk { reason: "a".into(), range: Some(6..8) },
+            ],
+        };
+        assert_eq!(confirmation.reasons(), ["a", "b"]);
+    }
+}
@@ -0,0 +1,737 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use ratatui::Frame;
+use tui_textarea::{CursorMove, Input, Key, TextArea};
+
+use crate::cheat;
+use crate::completion::{self, Listed, Listing, PathOptions};
+use crate::generator::Generated;
+use crate::history::History;
+use crate::shell::Shell;
+use crate::template::{self, Output};
+use crate::ui::commands::{Command, Variable, VariableType};
+use crate::ui::suggestions::Suggestions;
+
+/// Height of each input, with its borders.
+const FIELD_HEIGHT: u16 = 3;
+
+/// Inputs for the placeholders of a command, with a preview of the command as it is filled.
+#[derive(Debug)]
+pub struct Form {
+    pub command: Command,
+    /// Shell the values are quoted for.
+    pub shell: Shell,
+    fields: Vec<Field>,
+    focus: usize,
+    /// Whether the user tried to accept, empty required fields are only marked after that.
+    attempted: bool,
+}
+
+#[derive(Debug)]
+struct Field {
+    /// Declaration of the variable, placeholders without one take any value.
+    variable: Variable,
+    input: TextArea<'static>,
+    /// Whether hidden files are offered, for path variables.
+    hidden: bool,
+    generation: Generation,
+    /// Values given before, offered ahead of the other suggestions.
+    remembered: Vec<String>,
+    /// Last path whose entries were asked for, for path variables.
+    listing: Option<Listing>,
+    suggestions: Suggestions,
+}
+
+/// State of the generator of a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Generation {
+    /// Not run yet, it runs when the field gets the focus.
+    Pending,
+    /// Running with the values of the other fields given.
+    Running { id: u64, env: HashMap<String, String> },
+    Done { env: HashMap<String, String> },
+    Failed { message: String, env: HashMap<String, String> },
+}
+
+impl Generation {
+    /// Values of the other fields given to the last run.
+    fn env(&self) -> Option<&HashMap<String, String>> {
+        match self {
+            Generation::Pending => None,
+            Generation::Running { env, .. } | Generation::Done { env } | Generation::Failed { env, .. } => Some(env),
+        }
+    }
+}
+
+impl Field {
+    fn value(&self) -> String {
+        self.input.lines().join("")
+    }
+
+    fn set_value(&mut self, value: &str) {
+        self.input = TextArea::new(vec![value.to_string()]);
+        self.input.move_cursor(CursorMove::End);
+        self.changed();
+    }
+
+    /// Entries offered for path variables, their generator takes precedence if they have one.
+    fn path_options(&self) -> Option<PathOptions> {
+        let directories_only = match self.variable.kind {
+            VariableType::Path => false,
+            VariableType::Directory => true,
+            _ => return None,
+        };
+
+        self.variable.generator.is_none().then_some(PathOptions {
+            hidden: self.hidden,
+            directories_only,
+        })
+    }
+
+    /// Updates the suggestions after the value changed, the entries of paths come later, see [`Form::take_listing`].
+    fn changed(&mut self) {
+        self.suggestions.filter(&self.value());
+    }
+
+    /// Suggests the values after the remembered ones.
+    fn offer(&mut self, values: Vec<String>) {
+        let mut offered = self.remembered.clone();
+        offered.extend(values.into_iter().filter(|value| !self.remembered.contains(value)));
+        self.suggestions.set_values(offered);
+    }
+
+    /// Whether the highlighted suggestion was given before.
+    fn remembers_selected(&self) -> bool {
+        self.suggestions.selected().is_some_and(|selected| self.remembered.iter().any(|value| value == selected))
+    }
+
+    /// Why the value can't be accepted, a missing value only counts once the user tried to accept.
+    fn error(&self, attempted: bool) -> Option<String> {
+        let value = self.value();
+        if value.is_empty() && !attempted {
+            return None;
+        }
+        self.variable.validate(&value).err()
+    }
+}
+
+/// Generator to run for a field, see [`Form::pending_generator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratorRequest {
+    pub variable: String,
+    pub command: String,
+    /// Values of the other fields.
+    pub env: HashMap<String, String>,
+}
+
+/// What the form wants after a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormEvent {
+    /// The command with its placeholders filled.
+    Accepted(String),
+    /// The command with its placeholders filled, to run without leaving.
+    Execute(String),
+    /// A value given before is no longer wanted.
+    Forget { variable: String, value: String },
+}
+
+impl Form {
+    pub fn new(command: Command) -> Self {
+        let fields = template::placeholders(&command.command)
+            .into_iter()
+            .map(|name| {
+                let variable = command
+                    .variables
+                    .iter()
+                    .find(|variable| variable.name == name)
+                    .cloned()
+                    .unwrap_or_else(|| Variable { name: name.to_string(), ..Default::default() });
+
+                let mut field = Field {
+                    input: TextArea::default(),
+                    hidden: false,
+                    generation: Generation::Pending,
+                    remembered: vec![],
+                    listing: None,
+                    suggestions: Suggestions::default(),
+                    variable,
+                };
+                if let VariableType::Enum(choices) = &field.variable.kind {
+                    field.suggestions.set_values(choices.clone());
+                }
+                match field.variable.default.clone() {
+                    Some(default) => field.set_value(&default),
+                    None => field.changed(),
+                }
+                field
+            })
+            .collect();
+
+        Self { command, shell: Shell::default(), fields, focus: 0, attempted: false }
+    }
+
+    /// Offers the values given before to the variables of the command, invalid ones are left out.
+    pub fn remember(&mut self, history: &History) {
+        for field in &mut self.fields {
+            field.remembered = history
+                .values(&self.command.command, &field.variable.name)
+                .into_iter()
+                .filter(|value| field.variable.validate(value).is_ok())
+                .collect();
+
+            let values = match &field.variable.kind {
+                VariableType::Enum(choices) => choices.clone(),
+                _ => vec![],
+            };
+            field.offer(values);
+            field.changed();
+        }
+    }
+
+    /// Whether the command has no placeholders to fill.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Values typed so far, empty fields are left out.
+    pub fn values(&self) -> HashMap<String, String> {
+        self.fields
+            .iter()
+            .map(|field| (field.variable.name.clone(), field.value()))
+            .filter(|(_, value)| !value.is_empty())
+            .collect()
+    }
+
+    /// Generator of the focused field, if it never ran or the other values changed since it did.
+    ///
+    /// The caller runs it and reports back with [`Form::started`].
+    pub fn pending_generator(&self) -> Option<GeneratorRequest> {
+        let field = self.fields.get(self.focus)?;
+        let command = field.variable.generator.as_ref()?;
+
+        let mut env = self.values();
+        env.remove(&field.variable.name);
+        if field.generation.env() == Some(&env) {
+            return None;
+        }
+
+        Some(GeneratorRequest {
+            variable: field.variable.name.clone(),
+            command: command.clone(),
+            env,
+        })
+    }
+
+    /// Path typed in the focused field, if its entries weren't asked for yet.
+    ///
+    /// The caller lists them with [`completion::list`] and reports back with [`Form::listed`].
+    pub fn take_listing(&mut self) -> Option<Listing> {
+        let field = self.fields.get_mut(self.focus)?;
+        let listing = Listing {
+            variable: field.variable.name.clone(),
+            text: field.value(),
+            options: field.path_options()?,
+        };
+        if field.listing.as_ref() == Some(&listing) {
+            return None;
+        }
+
+        field.listing = Some(listing.clone());
+        Some(listing)
+    }
+
+    /// Offers the entries listed, unless the field changed since they were asked for.
+    pub fn listed(&mut self, listed: Listed) {
+        if let Some(field) = self.fields.iter_mut().find(|field| field.listing.as_ref() == Some(&listed.listing)) {
+            field.offer(listed.entries);
+            field.changed();
+        }
+    }
+
+    /// The generator of the request is running with the given identifier.
+    pub fn started(&mut self, request: GeneratorRequest, id: u64) {
+        if let Some(field) = self.fields.iter_mut().find(|field| field.variable.name == request.variable) {
+            field.generation = Generation::Running { id, env: request.env };
+        }
+    }
+
+    /// Takes the output of a generator, unless a newer run was started since.
+    pub fn generated(&mut self, generated: Generated) {
+        let Some(field) = self.fields.iter_mut().find(|field| field.variable.name == generated.variable) else {
+            return;
+        };
+        let Generation::Running { id, env } = &field.generation else {
+            return;
+        };
+        if *id != generated.id {
+            return;
+        }
+
+        let env = env.clone();
+        field.generation = match generated.result {
+            Ok(values) => {
+                field.offer(values);
+                Generation::Done { env }
+            },
+            Err(message) => Generation::Failed { message, env },
+        };
+    }
+
+    /// Command with the values typed so far.
+    pub fn rendered(&self) -> String {
+        template::render(&self.command.command, &self.values(), self.shell)
+    }
+
+    /// Command with every placeholder replaced, the empty ones too, unless some value is invalid.
+    ///
+    /// The focus moves to the first invalid field, which shows its error.
+    pub fn accept(&mut self) -> Option<String> {
+        self.attempted = true;
+        if let Some(invalid) = self.fields.iter().position(|field| field.error(true).is_some()) {
+            self.focus = invalid;
+            return None;
+        }
+
+        let values = self.fields.iter().map(|field| (field.variable.name.clone(), field.value())).collect();
+        Some(template::render(&self.command.command, &values, self.shell))
+    }
+
+    fn next(&mut self) {
+        if !self.fields.is_empty() {
+            self.focus = (self.focus + 1) % self.fields.len();
+        }
+    }
+
+    fn previous(&mut self) {
+        if !self.fields.is_empty() {
+            self.focus = (self.focus + self.fields.len() - 1) % self.fields.len();
+        }
+    }
+
+    pub fn key(&mut self, key: KeyEvent) -> Option<FormEvent> {
+        let field = self.fields.get_mut(self.focus);
+        let suggested = field.as_ref().is_some_and(|field| !field.suggestions.filtered().is_empty());
+
+        match (key.code, field) {
+            // Picks the highlighted suggestion and moves on, unless it is a folder to look into.
+            (KeyCode::Enter, Some(field)) if field.suggestions.selected().is_some() => {
+                let value = field.suggestions.selected().unwrap_or_default().to_string();
+                field.set_value(&value);
+                let folder = field.path_options().is_some() && value.ends_with('/');
+                if !folder && self.focus + 1 < self.fields.len() {
+                    self.next();
+                }
+            },
+            (KeyCode::Enter, _) => return self.accept().map(FormEvent::Accepted),
+            (KeyCode::Char('o'), _) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return self.accept().map(FormEvent::Execute);
+            },
+            (KeyCode::Delete, Some(field)) if field.remembers_selected() => {
+                let value = field.suggestions.selected().unwrap_or_default().to_string();
+                field.remembered.retain(|remembered| *remembered != value);
+                let values = field.suggestions.values().iter().filter(|offered| **offered != value).cloned().collect();
+                field.suggestions.set_values(values);
+                return Some(FormEvent::Forget { variable: field.variable.name.clone(), value });
+            },
+            (KeyCode::Down, Some(field)) if suggested => field.suggestions.down(),
+            (KeyCode::Up, Some(field)) if suggested => field.suggestions.up(),
+            (KeyCode::Tab, Some(field)) if field.path_options().is_some() => {
+                let options = field.path_options().unwrap_or_default();
+                match completion::complete(&field.value(), options) {
+                    Some(completed) => field.set_value(&completed),
+                    None => self.next(),
+                }
+            },
+            (KeyCode::Char('t'), Some(field)) if key.modifiers.contains(KeyModifiers::CONTROL) && field.path_options().is_some() => {
+                field.hidden = !field.hidden;
+            },
+            (KeyCode::Tab | KeyCode::Down, _) => self.next(),
+            (KeyCode::BackTab | KeyCode::Up, _) => self.previous(),
+            (_, Some(field)) => match Input::from(key) {
+                // Values are a single line.
+                Input { key: Key::Char('m'), ctrl: true, .. } => {},
+                input => {
+                    if field.input.input(input) {
+                        field.changed();
+                    }
+                },
+            },
+            (_, None) => {},
+        }
+
+        None
+    }
+
+    /// Command with the values typed so far highlighted, and the placeholders still empty dimmed.
+    fn preview(&self) -> Text<'static> {
+        let template = match template::parse(&self.command.command) {
+            Ok(template) => template,
+            Err(err) => return Text::styled(format!("Invalid template: {}", err.message), Style::default().fg(Color::Red)),
+        };
+        let values = self.values();
+        let focused = self.fields.get(self.focus).map(|field| field.variable.name.as_str());
+
+        let mut lines = vec![];
+        let mut spans = vec![];
+        for output in template.evaluate(&values, self.shell) {
+            let style = match &output {
+                Output::Text(_) => Style::default(),
+                Output::Value { .. } => Style::default().fg(Color::Indexed(220)),
+                Output::Missing(_) => Style::default().fg(Color::DarkGray),
+            };
+            let style = match &output {
+                Output::Value { name, .. } | Output::Missing(name) if Some(*name) == focused => {
+                    style.add_modifier(Modifier::UNDERLINED)
+                },
+                _ => style,
+            };
+            let text = output.text();
+
+            // Commands and values can span several lines.
+            let mut text_lines = text.split('\n');
+            if let Some(first) = text_lines.next() {
+                spans.push(Span::styled(first.to_string(), style));
+            }
+            for line in text_lines {
+                lines.push(Line::from(std::mem::take(&mut spans)));
+                spans.push(Span::styled(line.to_string(), style));
+            }
+        }
+        lines.push(Line::from(spans));
+
+        Text::from(lines)
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let preview_height = (self.command.command.lines().count() as u16 + 2).max(FIELD_HEIGHT);
+        let [preview, fields] =
+            Layout::vertical([Constraint::Length(preview_height), Constraint::Fill(1)]).areas(area);
+
+        let title = if self.command.tags.iter().any(|tag| tag == cheat::INTERACTIVE) {
+            "Preview (Enter prints, Ctrl-O runs in a terminal)"
+        } else {
+            "Preview (Enter prints, Ctrl-O runs)"
+        };
+        let block = Block::default().borders(Borders::ALL).title(title);
+        frame.render_widget(Paragraph::new(self.preview()).wrap(Wrap { trim: false }).block(block), preview);
+
+        // Suggestions of the focused field go below the fields, if it has any.
+        let focused = self.fields.get(self.focus);
+        let suggested = focused.is_some_and(|field| {
+            field.generation != Generation::Pending
+                || !field.remembered.is_empty()
+                || field.path_options().is_some()
+                || matches!(field.variable.kind, VariableType::Enum(_))
+        });
+        let wanted = self.fields.len() as u16 * FIELD_HEIGHT;
+        let [fields, suggestions] = if suggested {
+            let height = wanted.min(fields.height.saturating_sub(FIELD_HEIGHT).max(FIELD_HEIGHT));
+            Layout::vertical([Constraint::Length(height), Constraint::Fill(1)]).areas(fields)
+        } else {
+            [fields, Rect::default()]
+        };
+
+        if let Some(field) = focused.filter(|_| suggested) {
+            let block = match &field.generation {
+                Generation::Running { .. } => Block::default().title("Loading…"),
+                Generation::Failed { message, .. } => Block::default()
+                    .title(format!("Generator failed: {}", message))
+                    .title_style(Style::default().fg(Color::Red)),
+                _ if matches!(field.variable.kind, VariableType::Enum(_)) => Block::default().title("Choices"),
+                _ if field.path_options().is_some() => {
+                    let entries = if field.variable.kind == VariableType::Directory { "Folders" } else { "Files" };
+                    let hidden = if field.hidden { "hide" } else { "show" };
+                    Block::default().title(format!("{} (Tab completes, Ctrl-T to {} hidden)", entries, hidden))
+                },
+                _ => Block::default().title("Suggestions"),
+            };
+            let block = if field.remembers_selected() { block.title_bottom("Delete to forget") } else { block };
+            field.suggestions.render(frame, suggestions, block);
+        }
+
+        // Only the fields that fit are shown, keeping the focused one in view.
+        let visible = ((fields.height / FIELD_HEIGHT) as usize).max(1);
+        let first = (self.focus + 1).saturating_sub(visible);
+        let areas = Layout::vertical(vec![Constraint::Length(FIELD_HEIGHT); visible]).split(fields);
+
+        let attempted = self.attempted;
+        for ((n, field), area) in self.fields.iter_mut().enumerate().skip(first).zip(areas.iter()) {
+            let error = field.error(attempted);
+            let style = match (&error, n == self.focus) {
+                (Some(_), _) => Style::default().fg(Color::Red),
+                (None, true) => Style::default().fg(Color::Yellow),
+                (None, false) => Style::default(),
+            };
+            let title = match error {
+                Some(error) => format!("{}: {}", field.variable.name, error),
+                None => field.variable.name.clone(),
+            };
+            field.input.set_block(Block::default().borders(Borders::ALL).title(title).border_style(style));
+            field.input.set_cursor_style(if n == self.focus {
+                Style::default().reversed()
+            } else {
+                Style::default()
+            });
+            frame.render_widget(&field.input, *area);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(form: &mut Form, code: KeyCode) -> Option<FormEvent> {
+        form.key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn write(form: &mut Form, text: &str) {
+        for c in text.chars() {
+            press(form, KeyCode::Char(c));
+        }
+    }
+
+    /// Lists the entries of the focused field right away, the application does it in the background.
+    fn list(form: &mut Form) {
+        if let Some(listing) = form.take_listing() {
+            let entries = completion::entries(&listing.text, listing.options);
+            form.listed(Listed { listing, entries });
+        }
+    }
+
+    #[test]
+    fn test_form() {
+        let mut form = Form::new(Command {
+            command: "cp <src> <dst> && ls <dst>".into(),
+            tags: vec![],
+            description: String::new(),
+            variables: vec![],
+            source: None,
+        });
+
+        write(&mut form, "a.txt");
+        press(&mut form, KeyCode::Tab);
+        write(&mut form, "/tmp");
+        assert_eq!(form.rendered(), "cp a.txt /tmp && ls /tmp");
+
+        // Back to the first field, and around to the last one.
+        press(&mut form, KeyCode::BackTab);
+        press(&mut form, KeyCode::Backspace);
+        press(&mut form, KeyCode::BackTab);
+        write(&mut form, "p");
+        assert_eq!(form.rendered(), "cp a.tx /tmpp && ls /tmpp");
+
+        assert_eq!(press(&mut form, KeyCode::Enter), Some(FormEvent::Accepted("cp a.tx /tmpp && ls /tmpp".into())));
+        assert_eq!(
+            form.key(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::CONTROL)),
+            Some(FormEvent::Execute("cp a.tx /tmpp && ls /tmpp".into()))
+        );
+    }
+
+    #[test]
+    fn test_template() {
+        let mut form = Form::new(Command {
+            command: "tar {{#if verbose}}-v{{/if}}czf {{archive | default \"backup.tgz\"}} {{folder | shellquote}}".into(),
+            tags: vec![],
+            description: String::new(),
+            variables: vec![],
+            source: None,
+        });
+        assert_eq!(form.rendered(), "tar czf backup.tgz <folder>");
+
+        press(&mut form, KeyCode::Tab);
+        press(&mut form, KeyCode::Tab);
+        write(&mut form, "my docs");
+        assert_eq!(form.rendered(), "tar czf backup.tgz 'my docs'");
+
+        press(&mut form, KeyCode::Tab);
+        write(&mut form, "y");
+        assert_eq!(press(&mut form, KeyCode::Enter), Some(FormEvent::Accepted("tar -vczf backup.tgz 'my docs'".into())));
+    }
+
+    #[test]
+    fn test_generator() {
+        let mut form = Form::new(Command {
+            command: "git checkout <branch> -- <file>".into(),
+            tags: vec![],
+            description: String::new(),
+            variables: vec![Variable {
+                name: "branch".into(),
+                generator: Some("git branch".into()),
+                ..Default::default()
+            }],
+            source: None,
+        });
+
+        let request = form.pending_generator().unwrap();
+        assert_eq!(request, GeneratorRequest { variable: "branch".into(), command: "git branch".into(), env: HashMap::new() });
+        form.started(request, 2);
+        assert_eq!(form.pending_generator(), None);
+
+        // Results of older runs are dropped.
+        let result = Ok(vec!["main".into(), "feature/menu".into()]);
+        form.generated(Generated { id: 1, variable: "branch".into(), result: Ok(vec!["old".into()]) });
+        form.generated(Generated { id: 2, variable: "branch".into(), result });
+
+        write(&mut form, "men");
+        press(&mut form, KeyCode::Down);
+        press(&mut form, KeyCode::Enter);
+        assert_eq!(form.rendered(), "git checkout feature/menu -- <file>");
+
+        // The generator runs again when the values it depends on change.
+        assert_eq!(form.pending_generator(), None);
+        write(&mut form, "README.md");
+        press(&mut form, KeyCode::Tab);
+        assert_eq!(form.pending_generator().unwrap().env, HashMap::from([("file".into(), "README.md".into())]));
+    }
+
+    #[test]
+    fn test_types() {
+        let mut form = Form::new(Command {
+            command: "kubectl scale --replicas=<replicas> -n <env> <deployment>".into(),
+            tags: vec![],
+            description: String::new(),
+            variables: vec![
+                Variable {
+                    name: "replicas".into(),
+                    kind: VariableType::Integer,
+                    required: true,
+                    ..Default::default()
+                },
+                Variable {
+                    name: "env".into(),
+                    kind: VariableType::Enum(vec!["dev".into(), "prod".into()]),
+                    default: Some("dev".into()),
+                    ..Default::default()
+                },
+            ],
+            source: None,
+        });
+        assert_eq!(form.rendered(), "kubectl scale --replicas=<replicas> -n dev <deployment>");
+
+        // The missing value blocks the form, and the focus goes to it.
+        press(&mut form, KeyCode::Tab);
+        assert_eq!(press(&mut form, KeyCode::Enter), None);
+        assert_eq!(form.focus, 0);
+        assert_eq!(form.fields[0].error(form.attempted), Some("a value is required".into()));
+
+        write(&mut form, "three");
+        assert_eq!(press(&mut form, KeyCode::Enter), None);
+        assert_eq!(form.fields[0].error(form.attempted), Some("must be an integer".into()));
+
+        for _ in 0.."three".len() {
+            press(&mut form, KeyCode::Backspace);
+        }
+        write(&mut form, "3");
+
+        // Choices are picked from the list.
+        press(&mut form, KeyCode::Tab);
+        for _ in 0.."dev".len() {
+            press(&mut form, KeyCode::Backspace);
+        }
+        press(&mut form, KeyCode::Down);
+        press(&mut form, KeyCode::Down);
+        press(&mut form, KeyCode::Enter);
+
+        // Optional placeholders without a declaration are replaced by nothing.
+        assert_eq!(
+            press(&mut form, KeyCode::Enter),
+            Some(FormEvent::Accepted("kubectl scale --replicas=3 -n prod ".into()))
+        );
+    }
+
+    #[test]
+    fn test_paths() {
+        let root = std::env::temp_dir().join(format!("navu-form-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("backups/.old")).unwrap();
+        std::fs::write(root.join("backups/db.tar"), "").unwrap();
+        let root = format!("{}/", root.display());
+
+        let mut form = Form::new(Command {
+            command: "tar xf <archive> -C <target>".into(),
+            tags: vec![],
+            description: String::new(),
+            variables: vec![
+                Variable { name: "archive".into(), kind: VariableType::Path, ..Default::default() },
+                Variable { name: "target".into(), kind: VariableType::Directory, ..Default::default() },
+            ],
+            source: None,
+        });
+
+        // Tab completes the shared prefix, picking a folder stays in the field.
+        write(&mut form, &format!("{}back", root));
+        press(&mut form, KeyCode::Tab);
+        assert_eq!(form.fields[0].value(), format!("{}backups/", root));
+        list(&mut form);
+        assert_eq!(form.fields[0].suggestions.filtered(), vec![format!("{}backups/db.tar", root)]);
+        press(&mut form, KeyCode::Down);
+        press(&mut form, KeyCode::Enter);
+        assert_eq!(form.focus, 1);
+
+        // Only folders, hidden ones after Ctrl-T.
+        write(&mut form, &format!("{}backups/", root));
+        list(&mut form);
+        assert!(form.fields[1].suggestions.filtered().is_empty());
+        form.key(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL));
+        let listing = form.take_listing().unwrap();
+        assert!(form.take_listing().is_none());
+
+        // Entries listed for an older value are dropped.
+        write(&mut form, "x");
+        assert!(form.take_listing().is_some());
+        form.listed(Listed { listing, entries: vec![format!("{}backups/.old/", root)] });
+        assert!(form.fields[1].suggestions.filtered().is_empty());
+        press(&mut form, KeyCode::Backspace);
+        list(&mut form);
+        assert_eq!(form.fields[1].suggestions.filtered(), vec![format!("{}backups/.old/", root)]);
+        press(&mut form, KeyCode::Tab);
+        assert_eq!(form.rendered(), format!("tar xf {0}backups/db.tar -C {0}backups/.old/", root));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_remember() {
+        let command = "kubectl get pods -n <namespace>";
+        let mut history = History::default();
+        history.record_values(command, &HashMap::from([("namespace".into(), "dev".into())]), 1);
+        history.record_values("kubectl logs -n <namespace>", &HashMap::from([("namespace".into(), "prod".into())]), 2);
+
+        let mut form = Form::new(Command {
+            command: command.into(),
+            tags: vec![],
+            description: String::new(),
+            variables: vec![Variable {
+                name: "namespace".into(),
+                generator: Some("kubectl get namespaces".into()),
+                ..Default::default()
+            }],
+            source: None,
+        });
+        form.remember(&history);
+        assert_eq!(form.fields[0].suggestions.filtered(), ["dev", "prod"]);
+
+        // Remembered values go ahead of the generated ones.
+        let request = form.pending_generator().unwrap();
+        form.started(request, 1);
+        let result = Ok(vec!["default".into(), "prod".into()]);
+        form.generated(Generated { id: 1, variable: "namespace".into(), result });
+        assert_eq!(form.fields[0].suggestions.filtered(), ["dev", "prod", "default"]);
+
+        // Only remembered values can be forgotten.
+        press(&mut form, KeyCode::Down);
+        assert_eq!(
+            press(&mut form, KeyCode::Delete),
+            Some(FormEvent::Forget { variable: "namespace".into(), value: "dev".into() })
+        );
+        assert_eq!(form.fields[0].suggestions.filtered(), ["prod", "default"]);
+        press(&mut form, KeyCode::Down);
+        press(&mut form, KeyCode::Down);
+        assert_eq!(press(&mut form, KeyCode::Delete), None);
+    }
+}
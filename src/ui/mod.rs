@@ -7,6 +7,10 @@ pub mod state;
 pub mod search;
 pub mod template;
 pub mod commands;
+pub mod confirm;
+pub mod form;
+pub mod suggestions;
+pub mod terminal;
 
 /// Renders the user interface widgets.
 pub fn render(app: &mut App, frame: &mut Frame) {
@@ -12,6 +12,8 @@ use tui_textarea::{TextArea, CursorMove, Input, Key};
 #[derive(Debug)]
 pub struct SearchBox<'a> {
     textarea: TextArea<'a>,
+    /// Text of the search after the last edit, until it is taken by the matcher.
+    query: Option<String>,
 
     pub(crate) area: Option<Rect>,
 }
@@ -22,6 +24,7 @@ impl Default for SearchBox<'_> {
         textarea.set_block(Block::default().borders(Borders::ALL).title("Search"));
         Self {
             textarea,
+            query: None,
             area: None,
         }
     }
@@ -33,12 +36,27 @@ impl SearchBox<'_> {
         // restore previous input easily.
         self.textarea.move_cursor(CursorMove::End);
         self.textarea.delete_line_by_head();
+        self.query = Some(String::new());
+    }
+
+    /// Replaces the text of the search, on a single line.
+    pub fn set_text(&mut self, text: &str) {
+        let text = text.replace(['\n', '\r'], " ");
+        self.textarea.move_cursor(CursorMove::End);
+        self.textarea.delete_line_by_head();
+        self.textarea.insert_str(&text);
+        self.query = Some(text);
     }
 
     pub fn text(&self) -> String {
         self.textarea.lines().join("\n")
     }
 
+    /// New text of the search, if it was edited since the last call.
+    pub fn take_query(&mut self) -> Option<String> {
+        self.query.take()
+    }
+
     pub fn height(&self) -> u16 {
         3
     }
@@ -75,7 +93,9 @@ impl SearchBox<'_> {
 
 impl UIComponent for SearchBox<'_> {
     fn key(&mut self, key: KeyEvent) {
-        self.input(Input::from(key));
+        if let Some(query) = self.input(Input::from(key)) {
+            self.query = Some(query.to_string());
+        }
     }
 
     fn click(&mut self, _: MouseEvent) {}
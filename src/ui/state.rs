@@ -6,7 +6,7 @@ pub trait UIComponent {
     fn click(&mut self, event: MouseEvent);
 
     fn contains(&self, position: Position) -> bool {
-        self.area().map_or(false, |area| area.contains(position))
+        self.area().is_some_and(|area| area.contains(position))
     }
 
     fn area(&self) -> Option<Rect>;
@@ -0,0 +1,132 @@
+use nucleo::pattern::{CaseMatching, Normalization, Pattern};
+use nucleo::{Config, Matcher};
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::{Block, Borders, List, ListState};
+use ratatui::Frame;
+
+/// Values offered for a variable, fuzzy filtered with the text typed in its input.
+pub struct Suggestions {
+    values: Vec<String>,
+    /// Values matching the text, best first.
+    filtered: Vec<String>,
+    /// Value highlighted in the list, none until the user moves into it.
+    cursor: Option<usize>,
+    text: String,
+    matcher: Matcher,
+}
+
+impl Default for Suggestions {
+    fn default() -> Self {
+        Self {
+            values: vec![],
+            filtered: vec![],
+            cursor: None,
+            text: String::new(),
+            matcher: Matcher::new(Config::DEFAULT),
+        }
+    }
+}
+
+impl std::fmt::Debug for Suggestions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Suggestions")
+            .field("values", &self.values.len())
+            .field("filtered", &self.filtered.len())
+            .field("cursor", &self.cursor)
+            .finish()
+    }
+}
+
+impl Suggestions {
+    pub fn set_values(&mut self, values: Vec<String>) {
+        self.values = values;
+        self.refilter();
+    }
+
+    /// Every value, matching the text or not.
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Keeps the values that match the text, the cursor goes back out of the list.
+    pub fn filter(&mut self, text: &str) {
+        if text != self.text {
+            self.text = text.to_string();
+            self.refilter();
+        }
+    }
+
+    fn refilter(&mut self) {
+        let pattern = Pattern::parse(&self.text, CaseMatching::Smart, Normalization::Smart);
+        self.filtered = pattern
+            .match_list(&self.values, &mut self.matcher)
+            .into_iter()
+            .map(|(value, _)| value.clone())
+            .collect();
+        self.cursor = None;
+    }
+
+    /// Values matching the text, best first.
+    pub fn filtered(&self) -> &[String] {
+        &self.filtered
+    }
+
+    pub fn down(&mut self) {
+        if !self.filtered.is_empty() {
+            self.cursor = Some(self.cursor.map_or(0, |cursor| (cursor + 1).min(self.filtered.len() - 1)));
+        }
+    }
+
+    /// Moves up, leaving the list from its first value.
+    pub fn up(&mut self) {
+        self.cursor = self.cursor.and_then(|cursor| cursor.checked_sub(1));
+    }
+
+    /// Value under the cursor.
+    pub fn selected(&self) -> Option<&str> {
+        self.cursor.and_then(|cursor| self.filtered.get(cursor)).map(String::as_str)
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, block: Block<'_>) {
+        let list = List::new(self.filtered.iter().map(String::as_str))
+            .highlight_style(Style::default().bg(Color::Indexed(240)))
+            .block(block.borders(Borders::ALL));
+
+        let mut state = ListState::default().with_selected(self.cursor);
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggestions() {
+        let mut suggestions = Suggestions::default();
+        suggestions.set_values(vec!["main".into(), "feature/login".into(), "fix/main-menu".into()]);
+        assert_eq!(suggestions.filtered().len(), 3);
+        assert_eq!(suggestions.selected(), None);
+
+        suggestions.filter("main");
+        assert_eq!(suggestions.filtered(), ["main", "fix/main-menu"]);
+
+        suggestions.down();
+        suggestions.down();
+        suggestions.down();
+        assert_eq!(suggestions.selected(), Some("fix/main-menu"));
+
+        suggestions.up();
+        suggestions.up();
+        assert_eq!(suggestions.selected(), None);
+
+        suggestions.filter("nothing like it");
+        suggestions.down();
+        assert_eq!(suggestions.selected(), None);
+    }
+}
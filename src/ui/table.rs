@@ -15,45 +15,61 @@ use ratatui::{
     }, Frame
 };
 
+use std::sync::Arc;
+
+use crate::table::{Identity, Snapshot, Table as SharedTable};
 use crate::ui::state::UIComponent;
 
 #[derive(Debug, Clone)]
 pub struct CommandsTable<'a, T: Into<Row<'a>>> {
+    /// First row shown, rows are only converted for the window that fits in the area.
     offset: usize,
     cursor: Option<usize>,
 
-    content: Vec<T>,
+    /// Shared table where the search thread publishes its results
+    source: Arc<SharedTable<T>>,
+    /// Result set being shown, the latest one read from `source`
+    content: Arc<Snapshot<T>>,
     pub(crate) commands_in_view: usize,
+    /// Message shown in the title of the table, such as loading errors
+    pub(crate) status: Option<String>,
+    /// Row chosen with Enter, until it is taken
+    chosen: Option<T>,
     
     pub(crate) area: Option<Rect>,
     phantom: std::marker::PhantomData<&'a T>,
 }
 
-impl<'a, T: Into<Row<'a>> + Clone> Into<TableState> for &mut CommandsTable<'a, T> {
-    fn into(self) -> TableState {
+impl<'a, T: Into<Row<'a>> + Clone + Identity> From<&mut CommandsTable<'a, T>> for TableState {
+    fn from(table: &mut CommandsTable<'a, T>) -> Self {
+        // The table only gets the rows of the window.
         let mut state = TableState::default();
-        *state.offset_mut() = self.offset;
-        *state.selected_mut() = self.cursor;
+        *state.selected_mut() = table.cursor.and_then(|cursor| cursor.checked_sub(table.offset));
 
         state
     }
 }
 
-impl<'a, T: Into<Row<'a>> + Clone> Into<ScrollbarState> for &mut CommandsTable<'a, T> {
-    fn into(self) -> ScrollbarState {
+impl<'a, T: Into<Row<'a>> + Clone + Identity> From<&mut CommandsTable<'a, T>> for ScrollbarState {
+    fn from(table: &mut CommandsTable<'a, T>) -> Self {
         ScrollbarState::default()
-            .content_length(self.content.len())
-            .viewport_content_length(self.commands_in_view)
-            .position(self.cursor.unwrap_or(0))
+            .content_length(table.content.rows.len())
+            .viewport_content_length(table.commands_in_view)
+            .position(table.cursor.unwrap_or(0))
     }
 }
 
-impl<'a, T: Into<Row<'a>> + Clone> UIComponent for CommandsTable<'a, T> {
+impl<'a, T: Into<Row<'a>> + Clone + Identity> UIComponent for CommandsTable<'a, T> {
     fn area(&self) -> Option<Rect> {
         self.area
     }
 
     fn render(&mut self, frame: &mut Frame) {
+        self.refresh();
+        // Below the borders and the header.
+        self.commands_in_view = self.area.map_or(0, |area| area.height.saturating_sub(3) as usize);
+        self.follow_cursor();
+
         let mut table_state = self.into();
         let table = self.table();
 
@@ -74,6 +90,7 @@ impl<'a, T: Into<Row<'a>> + Clone> UIComponent for CommandsTable<'a, T> {
         }
 
         match key.code {
+            KeyCode::Up if self.content.rows.is_empty() => {},
             KeyCode::Up => {
                 if let Some(mut cursor) = self.cursor {
                     cursor = cursor.saturating_sub(1);
@@ -86,11 +103,12 @@ impl<'a, T: Into<Row<'a>> + Clone> UIComponent for CommandsTable<'a, T> {
                     self.cursor = Some(0);
                 }
             },
+            KeyCode::Down if self.content.rows.is_empty() => {},
             KeyCode::Down => {
                 if let Some(mut cursor) = self.cursor {
                     cursor = cursor.saturating_add(1);
-                    if cursor >= self.content.len() {
-                        cursor = self.content.len() - 1;
+                    if cursor >= self.content.rows.len() {
+                        cursor = self.content.rows.len() - 1;
                     }
 
                     self.cursor = Some(cursor);
@@ -99,35 +117,83 @@ impl<'a, T: Into<Row<'a>> + Clone> UIComponent for CommandsTable<'a, T> {
                 }
             },
             KeyCode::Enter => {
-                // Need to have a reference to the template widget to update the template
-                // with the selected command.
-            }  
+                // Without a cursor the first row is the best match.
+                self.chosen = self.selected().or(self.content.rows.first()).cloned();
+            },
             _ => {}
         }
     }
 }
 
-impl<'a, T: Into<Row<'a>> + Clone> CommandsTable<'a, T> {
-    pub fn new(rows: Vec<T>) -> Self
+impl<'a, T: Into<Row<'a>> + Clone + Identity> CommandsTable<'a, T> {
+    pub fn new(source: Arc<SharedTable<T>>) -> Self
     where T: Into<Row<'a>> {
         Self {
             offset: 0,
             cursor: None,
-            content: rows,
+            content: source.load(),
+            source,
             commands_in_view: 0,
+            status: None,
+            chosen: None,
             area: None,
             phantom: std::marker::PhantomData,
         }
     }
 
+    /// Picks up the latest result set published to the shared table.
+    ///
+    /// The cursor stays on the same command if it is still part of the results,
+    /// otherwise it is kept inside the new content.
+    pub fn refresh(&mut self) {
+        let latest = self.source.load();
+        if latest.version == self.content.version {
+            return;
+        }
+
+        let selected = self.selected().cloned();
+        self.content = latest;
+
+        let rows = &self.content.rows;
+        self.cursor = match (self.cursor, selected) {
+            _ if rows.is_empty() => None,
+            (Some(cursor), Some(selected)) => rows
+                .iter()
+                .position(|row| row.is(&selected))
+                .or(Some(cursor.min(rows.len() - 1))),
+            (cursor, _) => cursor.map(|cursor| cursor.min(rows.len() - 1)),
+        };
+        self.offset = self.offset.min(rows.len().saturating_sub(1));
+    }
+
+    /// Moves the window so the cursor is in it, keeping it full when the rows shrink.
+    fn follow_cursor(&mut self) {
+        let view = self.commands_in_view.max(1);
+        match self.cursor {
+            Some(cursor) if cursor < self.offset => self.offset = cursor,
+            Some(cursor) if cursor >= self.offset + view => self.offset = cursor + 1 - view,
+            _ => {},
+        }
+        self.offset = self.offset.min(self.content.rows.len().saturating_sub(view));
+    }
+
+    /// Row chosen with Enter, if one was chosen since the last call.
+    pub fn take_chosen(&mut self) -> Option<T> {
+        self.chosen.take()
+    }
+
+    /// Row under the cursor.
+    pub fn selected(&self) -> Option<&T> {
+        self.cursor.and_then(|cursor| self.content.rows.get(cursor))
+    }
+
+    /// Rows of the window shown, the others aren't converted.
     pub fn rows(&self) -> Vec<Row<'a>> {
-        let mut rows = self.content.iter().cloned().map(|row| row.into()).collect::<Vec<Row<'a>>>();
+        let window = self.content.rows.iter().skip(self.offset).take(self.commands_in_view);
+        let mut rows = window.cloned().map(|row| row.into()).collect::<Vec<Row<'a>>>();
 
-        if let Some(cursor) = self.cursor {
-            let selected = rows[cursor]
-                .clone()
-                .style(Style::default().bg(Color::Indexed(240)));
-            rows[cursor] = selected;
+        if let Some(row) = self.cursor.and_then(|cursor| rows.get_mut(cursor.checked_sub(self.offset)?)) {
+            *row = row.clone().style(Style::default().bg(Color::Indexed(240)));
         }
 
         rows
@@ -140,7 +206,7 @@ impl<'a, T: Into<Row<'a>> + Clone> CommandsTable<'a, T> {
             Constraint::Percentage(40),
         ];
 
-        return Table::new(self.rows(), constraints)
+        Table::new(self.rows(), constraints)
             .header(
                 Row::new(vec!["Command", "Tags", "Description"])
                     .style(Style::default().fg(Color::White).bg(Color::Indexed(240)))
@@ -148,13 +214,66 @@ impl<'a, T: Into<Row<'a>> + Clone> CommandsTable<'a, T> {
             )
             .highlight_spacing(ratatui::widgets::HighlightSpacing::Always)
             .flex(Flex::SpaceBetween)
-            .block(Block::bordered().border_type(BorderType::Thick))
+            .block(self.block())
     }
 
-    pub fn scroll(&self) -> Scrollbar {
+    fn block(&self) -> Block<'a> {
+        let block = Block::bordered().border_type(BorderType::Thick);
+
+        match &self.status {
+            Some(status) => block
+                .title(status.clone())
+                .title_style(Style::default().fg(Color::Red)),
+            None => block,
+        }
+    }
+
+    pub fn scroll(&self) -> Scrollbar<'_> {
         Scrollbar::default()
             .orientation(ScrollbarOrientation::VerticalRight)
             .begin_symbol(None)
             .end_symbol(None)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    use crate::ui::commands::Command;
+
+    fn command(n: usize) -> Command {
+        Command { command: format!("echo {}", n), tags: vec![], description: String::new(), variables: vec![], source: None }
+    }
+
+    #[test]
+    fn test_window() {
+        let source = Arc::new(SharedTable::default());
+        source.publish((0..100).map(command).collect());
+        let mut table = CommandsTable::new(source.clone());
+        table.refresh();
+        table.commands_in_view = 10;
+
+        // Only the rows in view are converted, the window follows the cursor both ways.
+        for _ in 0..15 {
+            table.key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        }
+        table.follow_cursor();
+        assert_eq!((table.cursor, table.offset), (Some(14), 5));
+        assert_eq!(table.rows().len(), 10);
+
+        for _ in 0..10 {
+            table.key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+        }
+        table.follow_cursor();
+        assert_eq!((table.cursor, table.offset), (Some(4), 4));
+
+        // Fewer results keep the window full.
+        source.publish((0..8).map(command).collect());
+        table.refresh();
+        table.follow_cursor();
+        assert_eq!((table.cursor, table.offset), (Some(4), 0));
+        assert_eq!(table.rows().len(), 8);
+    }
+}
@@ -1,43 +1,84 @@
+use crate::ui::commands::Command;
+use crate::ansi::Ansi;
+use crate::completion::{Listed, Listing};
+use crate::danger::Danger;
+use crate::execution::Progress;
+use crate::generator::Generated;
+use crate::history::History;
+use crate::shell::Shell;
+use crate::ui::confirm::Confirmation;
+use crate::ui::form::{Form, FormEvent, GeneratorRequest};
 use crate::ui::state::UIComponent;
 use crate::error::Error;
 
-use ratatui::text::Text;
+use ratatui::text::{Line, Text};
 use ratatui::widgets::{Block, Borders, Scrollbar, ScrollbarOrientation, ScrollbarState};
-use ratatui::style::{Modifier, Style};
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::layout::{Margin, Rect};
 use ratatui::Frame;
-use crossterm::event::{KeyEvent, MouseEvent, MouseEventKind};
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
+use std::collections::HashMap;
 use std::path::Path;
 use std::fs::read_to_string;
+use std::time::{Duration, Instant};
 
+/// Command accepted in the form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Accepted {
+    /// Command as written in the cheat, with its placeholders.
+    pub command: Command,
+    /// Values given to the placeholders, empty ones are left out.
+    pub values: HashMap<String, String>,
+    /// Command with its placeholders filled.
+    pub rendered: String,
+    /// Run it without leaving, instead of printing it.
+    pub execute: bool,
+}
+
+/// Command run from the form, its output shown as the document.
 #[derive(Debug)]
+struct Run {
+    command: String,
+    started: Instant,
+    /// Status, whether it succeeded, and how long it took.
+    exited: Option<(String, bool, Duration)>,
+    /// Colors of each output, they don't mix.
+    stdout: Ansi,
+    stderr: Ansi,
+}
+
+#[derive(Debug, Default)]
 pub struct Template {
     showing_document: bool,
     cursor: usize,
 
-    document: Vec<String>,
+    document: Vec<Line<'static>>,
     document_path: Option<String>,
+    /// Placeholders of the chosen command being filled.
+    form: Option<Form>,
+    /// Command accepted in the form, until it is taken.
+    accepted: Option<Accepted>,
+    /// Values the user asked to forget, as variable and value, until they are taken.
+    forgotten: Vec<(String, String)>,
+    /// Error to show in the status bar, until it is taken.
+    error: Option<String>,
     pub(crate) area: Option<Rect>,
+    /// Shell the values are quoted for.
+    pub(crate) shell: Shell,
+    /// Command whose output is shown.
+    run: Option<Run>,
+    /// Rules of the commands to confirm before they are accepted.
+    pub(crate) danger: Danger,
+    /// Command accepted in the form, waiting for the user to confirm its risks.
+    confirming: Option<Confirmation>,
 }
 
-impl Into<ScrollbarState> for &mut Template {
-    fn into(self) -> ScrollbarState {
+impl From<&mut Template> for ScrollbarState {
+    fn from(template: &mut Template) -> Self {
         ScrollbarState::default()
-            .content_length(self.document.len())
-            .viewport_content_length(self.area.unwrap().height.into())
-            .position(self.cursor)
-    }
-}
-
-impl Default for Template {
-    fn default() -> Self {
-        Self {
-            showing_document: false,
-            area: None,
-            document: vec![],
-            cursor: 0,
-            document_path: None,
-        }
+            .content_length(template.document.len())
+            .viewport_content_length(template.window_size())
+            .position(template.cursor)
     }
 }
 
@@ -56,8 +97,8 @@ impl Template {
         self.document_path = None;
     }
 
-    fn scroll(&self) -> Option<Scrollbar> {
-        if self.document.len() <= (self.area.unwrap().height - 2_u16) as usize {
+    fn scroll(&self) -> Option<Scrollbar<'_>> {
+        if self.document.len() <= self.window_size() {
             return None;
         }
 
@@ -67,30 +108,224 @@ impl Template {
             .end_symbol(None))
     }
 
-    fn text(&self) -> String {
-        let window_size = self.area.unwrap().height as usize;
-        let content_size = self.document.len();
+    /// Area the lines of the document are drawn in.
+    fn text_area(&self) -> Option<Rect> {
+        self.area.map(|area| area.inner(Margin::new(4, 2)))
+    }
+
+    /// Number of lines of the document shown at once.
+    fn window_size(&self) -> usize {
+        self.text_area().map_or(0, |area| area.height as usize)
+    }
 
-        if content_size <= window_size {
-            return self.document.join("\n");
+    fn text(&self) -> Text<'static> {
+        let from = self.cursor.min(self.bottom());
+        let to = (from + self.window_size()).min(self.document.len());
+        Text::from(self.document[from..to].to_vec())
+    }
+
+    /// Last position of the cursor, showing the end of the document.
+    fn bottom(&self) -> usize {
+        self.document.len().saturating_sub(self.window_size())
+    }
+
+    /// Scrolls the document with the keys, returns whether the key was one of them.
+    fn scroll_key(&mut self, key: KeyEvent) -> bool {
+        let page = self.window_size().max(1);
+        self.cursor = match key.code {
+            KeyCode::Up => self.cursor.saturating_sub(1),
+            KeyCode::Down => self.cursor.saturating_add(1),
+            KeyCode::PageUp => self.cursor.saturating_sub(page),
+            KeyCode::PageDown => self.cursor.saturating_add(page),
+            KeyCode::Home => 0,
+            KeyCode::End => self.bottom(),
+            _ => return false,
         }
+        .min(self.bottom());
+        true
+    }
 
-        let cursor_start = self.cursor;
-        let cursor_end = self.cursor.saturating_add(window_size);
+    /// Shows the output of the command about to run, in place of the document.
+    pub fn start_output(&mut self, command: &str) {
+        self.clear_document();
+        self.showing_document = true;
+        self.run = Some(Run {
+            command: command.to_string(),
+            started: Instant::now(),
+            exited: None,
+            stdout: Ansi::default(),
+            stderr: Ansi::default(),
+        });
+    }
 
-        let (from, to) = if cursor_end > content_size {
-            (content_size.saturating_sub(window_size), content_size.saturating_sub(1))
-        } else {
-            (cursor_start, cursor_end)
+    /// Adds the progress of the command run to its output, following it if the end is shown.
+    pub fn output(&mut self, progress: Progress) {
+        let Some(run) = &mut self.run else {
+            return;
         };
 
-        self.document[from..to].join("\n")
+        match progress {
+            Progress::Line { text, stderr } => {
+                let line = if stderr { run.stderr.line(&text) } else { run.stdout.line(&text) };
+                let following = self.cursor >= self.bottom();
+                self.document.push(line);
+                if following {
+                    self.cursor = self.bottom();
+                }
+            },
+            Progress::Exited { status, success, elapsed } => run.exited = Some((status, success, elapsed)),
+        }
+    }
+
+    /// Whether the output of a command is shown.
+    pub fn is_showing_output(&self) -> bool {
+        self.run.is_some()
+    }
+
+    /// Whether the command whose output is shown hasn't exited yet.
+    pub fn is_running(&self) -> bool {
+        self.run.as_ref().is_some_and(|run| run.exited.is_none())
+    }
+
+    /// Stops showing the output of the command run.
+    pub fn close_output(&mut self) {
+        if self.run.take().is_some() {
+            self.showing_document = false;
+            self.clear_document();
+        }
+    }
+
+    /// Title of the output, with the state of the command, and its color.
+    fn output_title(run: &Run) -> (String, Color) {
+        match &run.exited {
+            None => {
+                let elapsed = run.started.elapsed().as_secs_f32();
+                (format!("Output: {} (running {:.1}s, Ctrl-C to kill)", run.command, elapsed), Color::Yellow)
+            },
+            Some((status, success, elapsed)) => {
+                let color = if *success { Color::Green } else { Color::Red };
+                (format!("Output: {} ({} after {:.1}s, Esc to close)", run.command, status, elapsed.as_secs_f32()), color)
+            },
+        }
+    }
+
+    /// Shows the inputs for the placeholders of the command, offering the values given before.
+    pub fn fill(&mut self, command: Command, history: &History) {
+        let mut form = Form::new(command);
+        form.shell = self.shell;
+        form.remember(history);
+        self.form = Some(form);
+    }
+
+    /// Whether a command is being filled.
+    pub fn is_filling(&self) -> bool {
+        self.form.is_some()
+    }
+
+    /// Drops the command being filled.
+    pub fn close(&mut self) {
+        self.form = None;
+        self.confirming = None;
+    }
+
+    /// Whether a dangerous command waits to be confirmed.
+    pub fn is_confirming(&self) -> bool {
+        self.confirming.is_some()
+    }
+
+    /// Generator the form needs to run, see [`Form::pending_generator`].
+    pub fn pending_generator(&self) -> Option<GeneratorRequest> {
+        self.form.as_ref().and_then(Form::pending_generator)
+    }
+
+    pub fn started(&mut self, request: GeneratorRequest, id: u64) {
+        if let Some(form) = &mut self.form {
+            form.started(request, id);
+        }
+    }
+
+    pub fn generated(&mut self, generated: Generated) {
+        if let Some(form) = &mut self.form {
+            form.generated(generated);
+        }
+    }
+
+    /// Path the form needs listed, see [`Form::take_listing`].
+    pub fn take_listing(&mut self) -> Option<Listing> {
+        self.form.as_mut().and_then(Form::take_listing)
+    }
+
+    pub fn listed(&mut self, listed: Listed) {
+        if let Some(form) = &mut self.form {
+            form.listed(listed);
+        }
+    }
+
+    /// Command accepted in the form, if it was accepted since the last call.
+    pub fn take_accepted(&mut self) -> Option<Accepted> {
+        self.accepted.take()
+    }
+
+    /// Accepts the command right away if it has no placeholders to fill.
+    pub fn accept_if_complete(&mut self) {
+        let Some(form) = self.form.as_mut().filter(|form| form.is_empty()) else {
+            return;
+        };
+        let event = form.accept().map(FormEvent::Accepted);
+        self.form_event(event);
+    }
+
+    fn form_event(&mut self, event: Option<FormEvent>) {
+        let Some(form) = &self.form else {
+            return;
+        };
+
+        let (rendered, execute) = match event {
+            Some(FormEvent::Accepted(rendered)) => (rendered, false),
+            Some(FormEvent::Execute(rendered)) => (rendered, true),
+            Some(FormEvent::Forget { variable, value }) => return self.forgotten.push((variable, value)),
+            None => return,
+        };
+        let values = form.values();
+        let command = form.command.clone();
+        let accepted = Accepted { command, values, rendered, execute };
+
+        // The form stays open behind the confirmation, to go back to it.
+        let risks = self.danger.check(&accepted.rendered, &accepted.command.tags);
+        if !risks.is_empty() {
+            self.confirming = Some(Confirmation { accepted, risks });
+            return;
+        }
+        self.accepted = Some(accepted);
+        self.form = None;
+    }
+
+    /// Accepts the dangerous command with Enter or `y`, or goes back to the form with Esc or `n`.
+    fn confirm(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('y') => {
+                self.accepted = self.confirming.take().map(|confirmation| confirmation.accepted);
+                self.form = None;
+            },
+            KeyCode::Esc | KeyCode::Char('n') => self.confirming = None,
+            _ => {},
+        }
+    }
+
+    /// Values to forget, as variable and value, since the last call.
+    pub fn take_forgotten(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.forgotten)
+    }
+
+    /// Error of the last action, if there was one since the last call.
+    pub fn take_error(&mut self) -> Option<String> {
+        self.error.take()
     }
 
     pub fn show_document(&mut self, path: &Path) {
         let maybe_document = read_to_string(path)
-            .map(|content| content.lines().map(|line| line.to_string()).collect())
-            .map_err(|err| Error::Unknown(err));
+            .map(|content| content.lines().map(|line| Line::raw(line.to_string())).collect())
+            .map_err(Error::Unknown);
 
         match maybe_document {
             Ok(document) => {
@@ -103,15 +338,29 @@ impl Template {
             },
             Err(err) => {
                 self.clear_document();
-                println!("Error: {}", err);
+                self.error = Some(err.to_string());
             }
         }
     }
 }
 
 impl UIComponent for Template {
-    fn key(&mut self, _key: KeyEvent) {
-        self.show_document(Path::new("Cargo.lock"));
+    fn key(&mut self, key: KeyEvent) {
+        if self.confirming.is_some() {
+            return self.confirm(key);
+        }
+
+        let Some(form) = &mut self.form else {
+            if self.run.is_some() {
+                self.scroll_key(key);
+            } else {
+                self.show_document(Path::new("Cargo.lock"));
+            }
+            return;
+        };
+
+        let event = form.key(key);
+        self.form_event(event);
     }
 
     fn click(&mut self, event: MouseEvent) {
@@ -120,16 +369,11 @@ impl UIComponent for Template {
         }
 
         match event.kind {
-            MouseEventKind::ScrollUp => {
-                if self.cursor > 0 {
-                    self.cursor = self.cursor.saturating_sub(1);
-                }
+            MouseEventKind::ScrollUp if self.cursor > 0 => {
+                self.cursor = self.cursor.saturating_sub(1);
             },
             MouseEventKind::ScrollDown => {
-                self.cursor = self.cursor.saturating_add(1);
-                if self.cursor >= self.document.len() {
-                    self.cursor = self.document.len() - 1;
-                }
+                self.cursor = self.cursor.saturating_add(1).min(self.bottom());
             },
             _ => {}
         }
@@ -140,11 +384,37 @@ impl UIComponent for Template {
     }
 
     fn render(&mut self, frame: &mut Frame) {
-        let block = if self.showing_document {
+        if let Some(form) = &mut self.form {
+            let title = match form.command.description.as_str() {
+                "" => "Template".to_string(),
+                description => format!("Template: {}", description),
+            };
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_style(Style::default().add_modifier(Modifier::BOLD));
+
+            let area = self.area.unwrap();
+            frame.render_widget(block, area);
+            form.render(frame, area.inner(Margin::new(1, 1)));
+            if let Some(confirmation) = &self.confirming {
+                confirmation.render(frame, frame.area());
+            }
+            return;
+        }
+
+        let block = if let Some(run) = &self.run {
+            let (title, color) = Self::output_title(run);
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_style(Style::default().add_modifier(Modifier::BOLD))
+                .border_style(Style::default().fg(color))
+        } else if self.showing_document {
             let title = self.document_path.clone().unwrap();
             Block::default()
                 .borders(Borders::ALL)
-                .title(format!("{}", title))
+                .title(title)
                 .title_style(Style::default().add_modifier(Modifier::BOLD))
                 .border_style(Style::default())
         } else {
@@ -156,14 +426,48 @@ impl UIComponent for Template {
 
         frame.render_widget(block, self.area.unwrap());
         
-        if self.showing_document && self.document.len() > 0 {
+        if self.showing_document && !self.document.is_empty() {
             let mut state = self.into();
             if let Some(scrollbar) = self.scroll() {
                 frame.render_stateful_widget(scrollbar, self.area.unwrap(), &mut state);
             }
 
-            let textarea = Text::raw(self.text());
-            frame.render_widget(textarea, self.area.unwrap().inner(Margin::new(4, 2)));
+            let textarea = self.text();
+            frame.render_widget(textarea, self.text_area().unwrap());
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    #[test]
+    fn test_output() {
+        let mut template = Template { area: Some(Rect::new(0, 0, 40, 10)), ..Default::default() };
+        template.start_output("seq 20");
+        for number in 1..=20 {
+            template.output(Progress::Line { text: number.to_string(), stderr: false });
+        }
+
+        // The newest line is shown while following the output.
+        let lines = |template: &Template| template.text().lines.iter().map(|line| line.to_string()).collect::<Vec<_>>();
+        assert_eq!(lines(&template), ["15", "16", "17", "18", "19", "20"]);
+
+        template.scroll_key(KeyEvent::from(KeyCode::Home));
+        assert_eq!(lines(&template)[0], "1");
+        template.scroll_key(KeyEvent::from(KeyCode::End));
+        assert_eq!(lines(&template).last().map(String::as_str), Some("20"));
+    }
+
+    #[test]
+    fn test_scroll_empty_output() {
+        let mut template = Template { area: Some(Rect::new(0, 0, 40, 10)), ..Default::default() };
+        template.start_output("sleep 10");
+
+        let scroll = MouseEvent { kind: MouseEventKind::ScrollDown, column: 1, row: 1, modifiers: KeyModifiers::NONE };
+        template.click(scroll);
+        assert_eq!(template.cursor, 0);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::pty::Pty;
+use crate::ui::state::UIComponent;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Margin, Position, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, Clear};
+use ratatui::Frame;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+
+/// Pane with the terminal of an interactive command, in place of the Template and the results.
+#[derive(Debug, Default)]
+pub struct TerminalPane {
+    pty: Option<Pty>,
+    pub(crate) area: Option<Rect>,
+}
+
+impl TerminalPane {
+    /// Shows the terminal of the command.
+    pub fn open(&mut self, pty: Pty) {
+        self.pty = Some(pty);
+    }
+
+    /// Whether a command is shown.
+    pub fn is_open(&self) -> bool {
+        self.pty.is_some()
+    }
+
+    /// Command shown, if its identifier is the one given.
+    pub fn running(&self, id: u64) -> Option<&Pty> {
+        self.pty.as_ref().filter(|pty| pty.id == id)
+    }
+
+    /// Stops showing the terminal, killing its command if it is still running.
+    pub fn close(&mut self) {
+        self.pty = None;
+    }
+
+    /// Rows and columns inside the borders of the pane.
+    pub fn size(&self) -> (u16, u16) {
+        self.area
+            .map(|area| area.inner(Margin::new(1, 1)))
+            .filter(|inner| !inner.is_empty())
+            .map_or((24, 80), |inner| (inner.height, inner.width))
+    }
+}
+
+fn color(color: vt100::Color) -> Color {
+    match color {
+        vt100::Color::Default => Color::Reset,
+        vt100::Color::Idx(index) => Color::Indexed(index),
+        vt100::Color::Rgb(red, green, blue) => Color::Rgb(red, green, blue),
+    }
+}
+
+fn style(cell: &vt100::Cell) -> Style {
+    let mut style = Style::default().fg(color(cell.fgcolor())).bg(color(cell.bgcolor()));
+    for (enabled, modifier) in [
+        (cell.bold(), Modifier::BOLD),
+        (cell.italic(), Modifier::ITALIC),
+        (cell.underline(), Modifier::UNDERLINED),
+        (cell.inverse(), Modifier::REVERSED),
+    ] {
+        if enabled {
+            style = style.add_modifier(modifier);
+        }
+    }
+    style
+}
+
+/// Copies the screen of the emulator to the area of the buffer.
+fn draw_screen(screen: &vt100::Screen, buffer: &mut Buffer, area: Rect) {
+    for row in 0..area.height {
+        for column in 0..area.width {
+            // The second half of a wide character is drawn by the first one.
+            let Some(cell) = screen.cell(row, column).filter(|cell| !cell.is_wide_continuation()) else {
+                continue;
+            };
+
+            let contents = cell.contents();
+            let symbol = if contents.is_empty() { " " } else { contents.as_str() };
+            buffer[(area.x + column, area.y + row)].set_symbol(symbol).set_style(style(cell));
+        }
+    }
+}
+
+impl UIComponent for TerminalPane {
+    fn key(&mut self, key: KeyEvent) {
+        let Some(pty) = &mut self.pty else {
+            return;
+        };
+
+        // Every other key goes to the command, Ctrl-C and Esc included.
+        match key.code {
+            KeyCode::Char(']') if key.modifiers.contains(KeyModifiers::CONTROL) => pty.kill(),
+            _ => pty.key(key),
+        }
+    }
+
+    fn click(&mut self, _: MouseEvent) {}
+
+    fn area(&self) -> Option<Rect> {
+        self.area
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let (Some(pty), Some(area)) = (&mut self.pty, self.area) else {
+            return;
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Terminal: {} (Ctrl-] to kill)", pty.command))
+            .title_style(Style::default().add_modifier(Modifier::BOLD));
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        if inner.is_empty() {
+            return;
+        }
+
+        pty.resize((inner.height, inner.width));
+        let parser = pty.parser();
+        let screen = parser.screen();
+        draw_screen(screen, frame.buffer_mut(), inner);
+
+        if !screen.hide_cursor() {
+            let (row, column) = screen.cursor_position();
+            frame.set_cursor_position(Position::new(inner.x + column.min(inner.width - 1), inner.y + row.min(inner.height - 1)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_screen() {
+        let mut parser = vt100::Parser::new(2, 6, 0);
+        parser.process(b"a\x1b[1;38;5;208mb\x1b[0m\r\n\xe7\x95\x8c\x1b[48;2;1;2;3mc");
+
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 8, 3));
+        draw_screen(parser.screen(), &mut buffer, Rect::new(1, 1, 6, 2));
+
+        assert_eq!(buffer[(1, 1)].symbol(), "a");
+        assert_eq!(buffer[(2, 1)].symbol(), "b");
+        assert_eq!((buffer[(2, 1)].fg, buffer[(2, 1)].modifier), (Color::Indexed(208), Modifier::BOLD));
+        assert_eq!(buffer[(1, 2)].symbol(), "界");
+        assert_eq!(buffer[(3, 2)].symbol(), "c");
+        assert_eq!(buffer[(3, 2)].bg, Color::Rgb(1, 2, 3));
+    }
+}
@@ -0,0 +1,163 @@
+//! Key bindings for the shells, printed by `navu widget <shell>` to be sourced from the shell startup file.
+//!
+//! Each one binds Ctrl-G to open navu with the line being edited as the query, and replaces the line
+//! with the command chosen. The line is left as it was if navu is cancelled.
+
+use clap::ValueEnum;
+
+/// Shells with a line editor navu can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Widget {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+const BASH: &str = r#"# navu key binding for bash, add `eval "$(navu widget bash)"` to ~/.bashrc
+_navu_widget() {
+    local command
+    command="$(navu --query "$READLINE_LINE" </dev/tty)" || return
+    READLINE_LINE="$command"
+    READLINE_POINT=${#READLINE_LINE}
+}
+bind -x '"\C-g": _navu_widget'
+"#;
+
+const ZSH: &str = r#"# navu key binding for zsh, add `eval "$(navu widget zsh)"` to ~/.zshrc
+_navu_widget() {
+    local command
+    command="$(navu --query "$BUFFER" </dev/tty)"
+    if [[ $? -eq 0 ]]; then
+        BUFFER="$command"
+        CURSOR=${#BUFFER}
+    fi
+    zle reset-prompt
+}
+zle -N _navu_widget
+bindkey '^g' _navu_widget
+"#;
+
+const FISH: &str = r#"# navu key binding for fish, add `navu widget fish | source` to ~/.config/fish/config.fish
+function _navu_widget
+    set -l command (navu --query (commandline) | string collect)
+    and commandline --replace -- $command
+    commandline --function repaint
+end
+bind \cg _navu_widget
+if bind --mode insert >/dev/null 2>&1
+    bind --mode insert \cg _navu_widget
+end
+"#;
+
+impl Widget {
+    /// Script defining the key binding.
+    pub fn script(self) -> &'static str {
+        match self {
+            Widget::Bash => BASH,
+            Widget::Zsh => ZSH,
+            Widget::Fish => FISH,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::{Path, PathBuf};
+
+    use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+
+    /// Folder with a fake `navu` that chooses the query followed by `--chosen`, or cancels for `cancel`.
+    fn fake_navu(program: &str) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("navu-widget-{}-{}", program, std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+
+        let navu = folder.join("navu");
+        let script = "#!/bin/sh\n[ \"$1\" = --query ] || exit 2\n[ \"$2\" = cancel ] && exit 1\necho \"$2 --chosen\"\n";
+        std::fs::write(&navu, script).unwrap();
+        std::fs::set_permissions(&navu, std::fs::Permissions::from_mode(0o755)).unwrap();
+        folder
+    }
+
+    /// Lines written by the shell running the script in a terminal, as the widgets read `/dev/tty`.
+    ///
+    /// `None` if the shell isn't installed.
+    fn run(program: &str, arguments: &[&str], script: &str, path: &Path) -> Option<Vec<String>> {
+        let pair = native_pty_system().openpty(PtySize::default()).unwrap();
+        let mut builder = CommandBuilder::new(program);
+        builder.args(arguments);
+        builder.arg(script);
+        builder.env("PATH", format!("{}:{}", path.display(), std::env::var("PATH").unwrap_or_default()));
+        let mut child = pair.slave.spawn_command(builder).ok()?;
+        drop(pair.slave);
+
+        // The terminal reports an error instead of the end once the shell exits.
+        let mut output = vec![];
+        let _ = pair.master.try_clone_reader().unwrap().read_to_end(&mut output);
+        child.wait().unwrap();
+
+        Some(String::from_utf8_lossy(&output).lines().map(|line| line.trim_end_matches('\r').to_string()).collect())
+    }
+
+    /// Runs the widget in its shell for each line, the driver binds it, replaces `{line}` and prints the result.
+    fn check(program: &str, arguments: &[&str], driver: &str, binding: &str, cases: &[(&str, &str)]) {
+        let path = fake_navu(program);
+
+        for (line, expected) in cases {
+            let Some(output) = run(program, arguments, &driver.replace("{line}", line), &path) else {
+                eprintln!("{} is not installed, its widget is not tested", program);
+                break;
+            };
+            assert!(output.iter().any(|printed| printed == binding), "{}: {:?}", program, output);
+            assert!(output.contains(&format!("line: {}", expected)), "{} {}: {:?}", program, line, output);
+        }
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_bash() {
+        let driver = format!(
+            "{}\nbind -X\nREADLINE_LINE='{{line}}'; READLINE_POINT=2; _navu_widget; echo \"line: $READLINE_LINE|$READLINE_POINT\"",
+            Widget::Bash.script()
+        );
+        let cases = [("git st", "git st --chosen|15"), ("cancel", "cancel|2")];
+        check("bash", &["--norc", "--noprofile", "-c"], &driver, r#""\C-g": "_navu_widget""#, &cases);
+    }
+
+    #[test]
+    fn test_zsh() {
+        // Outside of the line editor `zle` can't redraw, and `BUFFER` and `CURSOR` are plain variables.
+        let driver = format!(
+            "{}\nbindkey '^g'\nzle() {{ :; }}\nBUFFER='{{line}}'; CURSOR=2; _navu_widget; echo \"line: $BUFFER|$CURSOR\"",
+            Widget::Zsh.script()
+        );
+        let cases = [("git st", "git st --chosen|15"), ("cancel", "cancel|2")];
+        check("zsh", &["-f", "-c"], &driver, r#""^G" _navu_widget"#, &cases);
+    }
+
+    #[test]
+    fn test_fish() {
+        // Outside of the line editor the command line is kept in a variable.
+        let driver = format!(
+            "{}\nbind \\cg | string match -q '*_navu_widget' && echo bound
+set -g line '{{line}}'
+function commandline
+    switch \"$argv[1]\"
+        case --replace
+            set -g line $argv[3]
+        case --function
+        case '*'
+            echo $line
+    end
+end
+_navu_widget
+echo \"line: $line\"",
+            Widget::Fish.script()
+        );
+        let cases = [("git st", "git st --chosen"), ("cancel", "cancel")];
+        check("fish", &["--no-config", "-c"], &driver, "bound", &cases);
+    }
+}